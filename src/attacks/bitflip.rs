@@ -0,0 +1,131 @@
+//! Bit-flipping attacks against unauthenticated encryption modes.
+
+use crate::{crypto::misc, Error, Result};
+
+/// Tampers with a CBC ciphertext so that the plaintext at `offset` changes
+/// from `known` to `desired`.
+///
+/// The difference is XORed into the preceding ciphertext block, which in turn
+/// decrypts to garbage. For this reason, `offset` cannot fall in the first block,
+/// and the modified bytes cannot span more than one block.
+pub fn cbc_flip(
+    ciphertext: &[u8],
+    bs: usize,
+    offset: usize,
+    known: &[u8],
+    desired: &[u8],
+) -> Result<Vec<u8>> {
+    if known.len() != desired.len() {
+        return Err(Error::InvalidArgument(
+            "Known and desired plaintexts differ in length",
+        ));
+    }
+    if offset < bs || offset / bs != (offset + known.len().max(1) - 1) / bs {
+        return Err(Error::InvalidArgument(
+            "Flipped bytes must lie in a single block, after the first one",
+        ));
+    }
+    if offset + known.len() > ciphertext.len() {
+        return Err(Error::InvalidArgument(
+            "Flipped bytes lie outside of the ciphertext",
+        ));
+    }
+
+    let mut tampered = ciphertext.to_vec();
+    let delta = misc::xor(known, desired);
+
+    for (c, d) in tampered[offset - bs..].iter_mut().zip(delta) {
+        *c ^= d;
+    }
+
+    Ok(tampered)
+}
+
+/// Tampers with a CTR ciphertext so that the plaintext at `offset` changes
+/// from `known` to `desired`.
+///
+/// Since the keystream is simply XORed into the plaintext, the difference is XORed
+/// directly into the ciphertext at the same offset, leaving every other byte intact.
+pub fn ctr_flip(ciphertext: &[u8], offset: usize, known: &[u8], desired: &[u8]) -> Result<Vec<u8>> {
+    if known.len() != desired.len() {
+        return Err(Error::InvalidArgument(
+            "Known and desired plaintexts differ in length",
+        ));
+    }
+    if offset + known.len() > ciphertext.len() {
+        return Err(Error::InvalidArgument(
+            "Flipped bytes lie outside of the ciphertext",
+        ));
+    }
+
+    let mut tampered = ciphertext.to_vec();
+    let delta = misc::xor(known, desired);
+
+    for (c, d) in tampered[offset..].iter_mut().zip(delta) {
+        *c ^= d;
+    }
+
+    Ok(tampered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::aes::{self, cbc, ctr};
+
+    #[test]
+    fn cbc_flip_works() {
+        let key = b"YELLOW SUBMARINE";
+        let ct = cbc::encrypt(b"We all live in a yellow submarine", key, None).unwrap();
+
+        let flipped = cbc_flip(&ct, 16, 17, b"yellow", b"purple").unwrap();
+        let pt = cbc::decrypt(flipped, key, None, true).unwrap();
+
+        assert_eq!(&pt[16..], b" purple submarine");
+    }
+
+    #[test]
+    fn cbc_flip_fails() {
+        let ct = cbc::encrypt(
+            b"We all live in a yellow submarine",
+            b"YELLOW SUBMARINE",
+            None,
+        )
+        .unwrap();
+
+        assert!(cbc_flip(&ct, 16, 3, b"all", b"ALL").is_err());
+        assert!(cbc_flip(&ct, 16, 30, b"marine", b"MARINE").is_err());
+        assert!(cbc_flip(&ct, 16, 17, b"yellow", b"red").is_err());
+    }
+
+    #[test]
+    fn cbc_flip_grants_admin() {
+        // The scrambled block is random, and may contain any separator
+        for seed in 0..500 {
+            let ct = aes::encrypt_comment_cbc("AAAAAAAAAAAAAAAA?admin?true?", seed).unwrap();
+            let flipped = cbc_flip(&ct, 16, 48, b"?admin?true?", b";admin=true;").unwrap();
+
+            assert!(aes::is_admin_cbc(flipped, seed).unwrap());
+        }
+    }
+
+    #[test]
+    fn ctr_flip_works() {
+        let key = b"YELLOW SUBMARINE";
+        let ct = ctr::encrypt(b"We all live in a yellow submarine", key, 0).unwrap();
+
+        let flipped = ctr_flip(&ct, 17, b"yellow", b"purple").unwrap();
+        let pt = ctr::decrypt(flipped, key, 0).unwrap();
+
+        assert_eq!(pt, &b"We all live in a purple submarine"[..]);
+    }
+
+    #[test]
+    fn ctr_flip_fails() {
+        let ct =
+            ctr::encrypt(b"We all live in a yellow submarine", b"YELLOW SUBMARINE", 0).unwrap();
+
+        assert!(ctr_flip(&ct, 30, b"marine", b"MARINE").is_err());
+        assert!(ctr_flip(&ct, 17, b"yellow", b"red").is_err());
+    }
+}
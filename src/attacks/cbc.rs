@@ -0,0 +1,64 @@
+//! Attacks against the CBC mode of operation.
+
+use crate::{crypto::misc, oracle::Oracle, Error, Result};
+
+/// Recovers the key of a CBC ciphertext encrypted using the key as IV.
+///
+/// The oracle decrypts its input and must fail with [`Error::InvalidPlaintext`]
+/// when the plaintext is rejected. Submitting `C1 || 0 || C1` makes the first and
+/// third plaintext blocks differ exactly by the IV, that is, the key. The last two
+/// ciphertext blocks are appended unchanged, so that the padding remains valid.
+///
+/// [`Error::InvalidPlaintext`]: ../../enum.Error.html#variant.InvalidPlaintext
+pub fn recover_iv_key<O, A>(oracle: &O, ciphertext: &[u8], bs: usize) -> Result<Vec<u8>>
+where
+    O: for<'a> Oracle<&'a [u8], Answer = A>,
+{
+    // `usize::is_multiple_of` needs Rust 1.87, which is too recent for this crate
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    if ciphertext.len() < 2 * bs || ciphertext.len() % bs != 0 {
+        return Err(Error::InvalidInputLength(ciphertext.len()));
+    }
+
+    let first = &ciphertext[..bs];
+    let crafted = [
+        first,
+        &vec![0; bs],
+        first,
+        &ciphertext[ciphertext.len() - 2 * bs..],
+    ]
+    .concat();
+
+    match oracle.query(&crafted) {
+        Err(Error::InvalidPlaintext(pt)) => Ok(misc::xor(&pt[..bs], &pt[2 * bs..3 * bs])),
+        Err(e) => Err(e),
+        Ok(_) => Err(Error::AttackFailed("Plaintext was not rejected")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::aes::{self, cbc},
+        oracle::FnOracle,
+    };
+
+    #[test]
+    fn recover_iv_key_works() {
+        let key = aes::random_key();
+        let ct = cbc::encrypt(&[b'A'; 48][..], key, Some(&key)).unwrap();
+
+        let oracle = FnOracle::new(|ct: &[u8]| {
+            let pt = cbc::decrypt(ct, key, Some(&key), true)?;
+            if pt.is_ascii() {
+                Ok(())
+            } else {
+                Err(Error::InvalidPlaintext(pt))
+            }
+        });
+
+        assert_eq!(recover_iv_key(&oracle, &ct, 16).unwrap(), key);
+        assert!(recover_iv_key(&oracle, &ct[..16], 16).is_err());
+    }
+}
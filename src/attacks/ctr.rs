@@ -0,0 +1,40 @@
+//! Attacks against the CTR mode of operation.
+
+use crate::{crypto::misc, oracle::EditOracle, Error, Result};
+
+/// Recovers the plaintext of a CTR ciphertext through an oracle that lets us edit it.
+///
+/// Overwriting the whole plaintext with zeros makes the oracle return the bare keystream,
+/// which decrypts the original ciphertext with a single query.
+pub fn decrypt_with_edit<O: EditOracle>(oracle: &O, ciphertext: &[u8]) -> Result<Vec<u8>> {
+    let keystream = oracle.query((0, &vec![0; ciphertext.len()]))?;
+
+    if keystream.len() != ciphertext.len() {
+        return Err(Error::AttackFailed("Edit changed the ciphertext length"));
+    }
+
+    Ok(misc::xor(ciphertext, &keystream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::aes::{self, ctr},
+        oracle::{FnOracle, Oracle},
+    };
+
+    #[test]
+    fn decrypt_with_edit_works() {
+        let key = aes::random_key();
+        let pt = b"We all live in a yellow submarine";
+        let ct = ctr::encrypt(&pt[..], key, 0).unwrap();
+
+        let oracle = FnOracle::new(|(offset, newtext): (usize, &[u8])| {
+            ctr::edit(&ct, key, 0, offset, newtext)
+        });
+
+        assert_eq!(decrypt_with_edit(&oracle, &ct).unwrap(), &pt[..]);
+        assert_eq!(oracle.queries(), 1);
+    }
+}
@@ -0,0 +1,188 @@
+//! Man-in-the-middle attacks against the Diffie-Hellman echo protocol.
+//!
+//! In both attacks, the attacker forces the shared secrets to predictable values,
+//! decrypts all the traffic and relays it so that neither party notices.
+
+use crate::{
+    crypto::{bigint::BigUint, dh},
+    network::Mitm,
+    protocols::dh_echo::Message,
+    Error, Result,
+};
+
+use num_traits::One;
+
+/// Malicious generators injected during group negotiation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaliciousG {
+    /// `g = 1`, forcing the shared secret to 1.
+    One,
+    /// `g = p`, forcing the shared secret to 0.
+    P,
+    /// `g = p - 1`, forcing the shared secret to either 1 or `p - 1`.
+    PMinusOne,
+}
+
+impl MaliciousG {
+    /// Returns the value of the generator for modulus `p`.
+    fn value(self, p: &BigUint) -> BigUint {
+        match self {
+            MaliciousG::One => BigUint::one(),
+            MaliciousG::P => p.clone(),
+            MaliciousG::PMinusOne => p - 1u32,
+        }
+    }
+}
+
+/// Performs the key-fixing attack against the echo protocol.
+///
+/// Both public keys are replaced with `p`, which makes both shared secrets 0.
+/// Returns the intercepted message and its echo.
+pub fn key_fixing(mitm: Mitm<Message>) -> Result<Vec<Vec<u8>>> {
+    let p = match mitm.a.recv()? {
+        Message::Params { p, g, .. } => {
+            mitm.b.send(Message::Params {
+                p: p.clone(),
+                g,
+                public: p.clone(),
+            })?;
+            p
+        }
+        _ => return Err(Error::Protocol("Expected group parameters")),
+    };
+
+    match mitm.b.recv()? {
+        Message::PublicKey(_) => mitm.a.send(Message::PublicKey(p))?,
+        _ => return Err(Error::Protocol("Expected a public key")),
+    }
+
+    // Both parties now use (p^x mod p) = 0 as their shared secret
+    let secret = BigUint::from(0u32);
+
+    let mut intercepted = vec![];
+    for from_a in &[true, false] {
+        let (src, dst) = if *from_a {
+            (&mitm.a, &mitm.b)
+        } else {
+            (&mitm.b, &mitm.a)
+        };
+
+        match src.recv()? {
+            Message::Data(data) => {
+                intercepted.push(dh::decrypt(&secret, &data)?);
+                dst.send(Message::Data(data))?;
+            }
+            _ => return Err(Error::Protocol("Expected encrypted data")),
+        }
+    }
+
+    Ok(intercepted)
+}
+
+/// Performs the malicious-generator attack against the negotiated echo protocol.
+///
+/// B is made to use the malicious generator `g'`, and receives `g'` as A's public key,
+/// so that its shared secret equals its own public key. A computes its secret from
+/// B's public key, which is a power of `g'` and thus takes one of few values.
+/// Since the secrets differ, traffic is re-encrypted in both directions.
+///
+/// Assumes that the messages are ASCII text, to tell apart candidate secrets.
+/// Returns the intercepted message and its echo.
+pub fn malicious_g(mitm: Mitm<Message>, g: MaliciousG) -> Result<Vec<Vec<u8>>> {
+    let p = match mitm.a.recv()? {
+        Message::Group { p, .. } => p,
+        _ => return Err(Error::Protocol("Expected group parameters")),
+    };
+    let g = g.value(&p);
+
+    mitm.b.send(Message::Group {
+        p: p.clone(),
+        g: g.clone(),
+    })?;
+
+    match mitm.b.recv()? {
+        Message::Ack => mitm.a.send(Message::Ack)?,
+        _ => return Err(Error::Protocol("Expected an acknowledgment")),
+    }
+
+    match mitm.a.recv()? {
+        Message::PublicKey(_) => mitm.b.send(Message::PublicKey(g))?,
+        _ => return Err(Error::Protocol("Expected a public key")),
+    }
+
+    let public_b = match mitm.b.recv()? {
+        Message::PublicKey(public) => public,
+        _ => return Err(Error::Protocol("Expected a public key")),
+    };
+    mitm.a.send(Message::PublicKey(public_b.clone()))?;
+
+    // B's secret is (g')^b, that is, its own public key. A's secret is B's public key
+    // raised to a, which is either 0, 1 or p - 1 depending on the parity of a.
+    let secret_b = public_b.clone();
+    let candidates_a = if public_b == &p - 1u32 {
+        vec![BigUint::one(), public_b]
+    } else {
+        vec![public_b]
+    };
+
+    let (secret_a, message) = match mitm.a.recv()? {
+        Message::Data(data) => candidates_a
+            .into_iter()
+            .find_map(|s| match dh::decrypt(&s, &data) {
+                Ok(m) if m.is_ascii() => Some((s, m)),
+                _ => None,
+            })
+            .ok_or(Error::AttackFailed(
+                "No candidate secret decrypts the message",
+            ))?,
+        _ => return Err(Error::Protocol("Expected encrypted data")),
+    };
+    mitm.b
+        .send(Message::Data(dh::encrypt(&secret_b, &message)?))?;
+
+    let echo = match mitm.b.recv()? {
+        Message::Data(data) => dh::decrypt(&secret_b, &data)?,
+        _ => return Err(Error::Protocol("Expected encrypted data")),
+    };
+    mitm.a.send(Message::Data(dh::encrypt(&secret_a, &echo)?))?;
+
+    Ok(vec![message, echo])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::dh::DhGroup,
+        network,
+        protocols::dh_echo::{initiator, negotiating_initiator, negotiating_responder, responder},
+    };
+
+    #[test]
+    fn key_fixing_works() {
+        let (echoed, received, intercepted) = network::run_with_mitm(
+            |ep| initiator(ep, DhGroup::nist(), b"Attack at dawn"),
+            responder,
+            key_fixing,
+        );
+
+        assert_eq!(echoed.unwrap(), b"Attack at dawn");
+        assert_eq!(received.unwrap(), b"Attack at dawn");
+        assert_eq!(intercepted.unwrap(), vec![b"Attack at dawn".to_vec(); 2]);
+    }
+
+    #[test]
+    fn malicious_g_works() {
+        for &g in &[MaliciousG::One, MaliciousG::P, MaliciousG::PMinusOne] {
+            let (echoed, received, intercepted) = network::run_with_mitm(
+                |ep| negotiating_initiator(ep, DhGroup::nist(), b"Attack at dawn"),
+                negotiating_responder,
+                |mitm| malicious_g(mitm, g),
+            );
+
+            assert_eq!(echoed.unwrap(), b"Attack at dawn");
+            assert_eq!(received.unwrap(), b"Attack at dawn");
+            assert_eq!(intercepted.unwrap(), vec![b"Attack at dawn".to_vec(); 2]);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Byte-at-a-time decryption of secrets appended to attacker-controlled ECB input.
+
+use crate::{
+    crypto::aes,
+    oracle::{EncryptionOracle, FnOracle},
+    Error, Result,
+};
+
+/// Decrypts the unknown suffix appended by an ECB encryption oracle, one byte at a time.
+///
+/// `oracle` must encrypt its input followed by the unknown suffix,
+/// using a block cipher in ECB mode with blocks of `bs` bytes.
+pub fn byte_at_a_time<O: EncryptionOracle>(oracle: &O, bs: usize) -> Result<Vec<u8>> {
+    // Allocate enough space for the deciphered text
+    let mut deciphered = vec![0u8; oracle.query(&[])?.len()];
+
+    // Break the ciphertext one byte at a time
+    for blk_id in 0..deciphered.len() / bs {
+        let base = blk_id * bs;
+        let end = (blk_id + 1) * bs;
+
+        for i in 0..bs {
+            let n = bs - i;
+
+            let mut test_vec = if blk_id == 0 {
+                [&vec![0; n - 1], &deciphered[..=i]].concat()
+            } else {
+                deciphered[(blk_id - 1) * bs + i + 1..base + i + 1].to_vec()
+            };
+
+            // This is the ciphertext we need to match
+            let hint = oracle.query(&vec![0; n - 1])?;
+
+            // Try every possible byte until the ciphertext matches
+            for byte in 0..=255 {
+                test_vec[bs - 1] = byte;
+
+                if oracle.query(&test_vec)?[..bs] == hint[base..end] {
+                    deciphered[base + i] = byte;
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(deciphered)
+}
+
+/// Returns the length of the unknown prefix prepended by an ECB encryption oracle.
+///
+/// The prefix is found by feeding the oracle two blocks' worth of identical bytes,
+/// preceded by an increasing amount of filler, until two identical ciphertext
+/// blocks show up: at that point, the identical bytes are block-aligned.
+///
+/// Trailing bytes of the prefix or leading bytes of the suffix that happen to match
+/// the input would make the blocks line up too early. Since they cannot match two
+/// different byte values, alignment is only accepted when it shows up at the same
+/// position for both.
+pub fn detect_prefix_length<O: EncryptionOracle>(oracle: &O, bs: usize) -> Result<usize> {
+    // Returns the indices of the blocks followed by an identical block
+    let aligned = |b: u8, k: usize| -> Result<Vec<usize>> {
+        let ct = oracle.query(&vec![b; k + 2 * bs])?;
+
+        Ok(ct
+            .chunks(bs)
+            .zip(ct.chunks(bs).skip(1))
+            .enumerate()
+            .filter(|(_, (x, y))| x == y)
+            .map(|(i, _)| i)
+            .collect())
+    };
+
+    for k in 0..bs {
+        let zeros = aligned(0x00, k)?;
+        let ones = aligned(0xff, k)?;
+
+        if let Some(i) = zeros.iter().find(|i| ones.contains(i)) {
+            return Ok(i * bs - k);
+        }
+    }
+
+    Err(Error::AttackFailed(
+        "Could not align the input to the block size",
+    ))
+}
+
+/// Decrypts the unknown suffix appended by an ECB encryption oracle, which also
+/// prepends an unknown, fixed-length prefix to its input.
+///
+/// The input is padded so that it starts on a block boundary, and the blocks
+/// covering the prefix are discarded; the result is then the same as attacking
+/// an oracle without prefix.
+pub fn byte_at_a_time_with_prefix<O: EncryptionOracle>(oracle: &O, bs: usize) -> Result<Vec<u8>> {
+    let prefix_len = detect_prefix_length(oracle, bs)?;
+
+    let pad = (bs - prefix_len % bs) % bs;
+    let skip = prefix_len + pad;
+
+    byte_at_a_time(
+        &FnOracle::new(|i: &[u8]| Ok(oracle.query(&[&vec![0; pad], i].concat())?[skip..].to_vec())),
+        bs,
+    )
+}
+
+/// Returns whether the oracle encrypts its input in ECB mode.
+pub fn is_ecb_oracle<O: EncryptionOracle>(oracle: &O, bs: usize) -> Result<bool> {
+    Ok(aes::is_ecb_encrypted(oracle.query(&vec![0; bs * 3])?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::{Oracle, OracleError};
+
+    #[test]
+    fn detect_prefix_length_works() {
+        let key = aes::random_key();
+
+        for len in 0..40 {
+            // Make the prefix end with bytes that match the probing input
+            let mut prefix = (0..len).map(|_| rand::random()).collect::<Vec<u8>>();
+            for b in prefix.iter_mut().skip(2 * len / 3) {
+                *b = 0;
+            }
+
+            let oracle =
+                FnOracle::new(|i: &[u8]| aes::ecb::encrypt([&prefix, i].concat(), key, true));
+
+            assert_eq!(detect_prefix_length(&oracle, 16).unwrap(), len);
+        }
+    }
+
+    #[test]
+    fn detect_prefix_length_with_matching_suffix() {
+        let key = aes::random_key();
+
+        for len in 0..40 {
+            // A prefix ending with one probe byte and a suffix starting with the other
+            let prefix = [&vec![0xaa; len / 2][..], &vec![0x00; len - len / 2]].concat();
+
+            for suffix in &[&b"\x00\x00\x00secret"[..], &b"\xff\xffsecret"[..]] {
+                let oracle = FnOracle::new(|i: &[u8]| {
+                    aes::ecb::encrypt([&prefix, i, suffix].concat(), key, true)
+                });
+
+                assert_eq!(detect_prefix_length(&oracle, 16).unwrap(), len);
+            }
+        }
+    }
+
+    #[test]
+    fn byte_at_a_time_respects_budget() {
+        let oracle = FnOracle::new(|i: &[u8]| aes::encrypt_seeded(i, 0)).with_budget(100);
+
+        assert_eq!(
+            byte_at_a_time(&oracle, 16).unwrap_err(),
+            Error::Oracle(OracleError::BudgetExhausted(100))
+        );
+        assert_eq!(oracle.queries(), 100);
+    }
+}
@@ -0,0 +1,121 @@
+//! Length-extension attacks against secret-prefix MACs built on Merkle–Damgård hashes.
+
+use crate::{
+    crypto::hash::Hash,
+    oracle::{MacOracle, MacQuery},
+    Error, Result,
+};
+
+/// Forges a secret-prefix MAC `H(key || message)` for `message || glue || extension`,
+/// given the valid MAC of `message`.
+///
+/// The length of the secret key is unknown, so every length up to `max_key_len`
+/// is tried in turn until the verifier oracle accepts the forgery.
+/// Returns the forged message and its MAC.
+pub fn forge<H: Hash, O: MacOracle>(
+    oracle: &O,
+    message: &[u8],
+    mac: &H::Output,
+    extension: &[u8],
+    max_key_len: usize,
+) -> Result<(Vec<u8>, H::Output)> {
+    for key_len in 0..=max_key_len {
+        let len = (key_len + message.len()) as u64;
+        let glue = H::padding(len);
+
+        // Resume hashing right after the padded original message
+        let mut hasher = H::from_digest(mac, len + glue.len() as u64)?;
+        hasher.update(extension);
+        let forged_mac = hasher.finalize();
+
+        let forged = [message, &glue, extension].concat();
+
+        if oracle.query(MacQuery {
+            message: &forged,
+            mac: forged_mac.as_ref(),
+        })? {
+            return Ok((forged, forged_mac));
+        }
+    }
+
+    Err(Error::AttackFailed("No key length produced a valid MAC"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::hash::{md4::Md4, md5::Md5, sha1::Sha1, sha256::Sha256},
+        oracle::{FnOracle, Oracle},
+    };
+
+    /// Computes the secret-prefix MAC `H(key || message)`.
+    fn mac<H: Hash>(key: &[u8], message: &[u8]) -> H::Output {
+        H::digest([key, message].concat())
+    }
+
+    fn forge_works<H: Hash>() {
+        let key = b"SECRET KEY";
+        let message = b"user=alice";
+
+        let oracle = FnOracle::new(|q: MacQuery| Ok(mac::<H>(key, q.message).as_ref() == q.mac));
+
+        let (forged, forged_mac) = forge::<H, _>(
+            &oracle,
+            message,
+            &mac::<H>(key, message),
+            b";admin=true",
+            32,
+        )
+        .unwrap();
+
+        assert!(forged.starts_with(message) && forged.ends_with(b";admin=true"));
+        assert_eq!(mac::<H>(key, &forged).as_ref(), forged_mac.as_ref());
+        assert_eq!(oracle.queries(), key.len() + 1);
+
+        assert!(forge::<H, _>(&oracle, message, &mac::<H>(key, message), b"!", 4).is_err());
+    }
+
+    fn resume_works<H: Hash>() {
+        let message = b"The quick brown fox jumps over the lazy dog";
+        let glue = H::padding(message.len() as u64);
+
+        let mut hasher =
+            H::from_digest(&H::digest(message), (message.len() + glue.len()) as u64).unwrap();
+        hasher.update(b"!");
+
+        assert_eq!(
+            hasher.finalize().as_ref(),
+            H::digest([&message[..], &glue, b"!"].concat()).as_ref()
+        );
+        assert!(H::from_state(H::default().state(), 10).is_err());
+    }
+
+    #[test]
+    fn resume_from_digest() {
+        resume_works::<Sha1>();
+        resume_works::<Sha256>();
+        resume_works::<Md4>();
+        resume_works::<Md5>();
+    }
+
+    #[test]
+    fn forge_sha1() {
+        forge_works::<Sha1>();
+    }
+
+    #[test]
+    fn forge_sha256() {
+        forge_works::<Sha256>();
+    }
+
+    #[test]
+    fn forge_md4() {
+        forge_works::<Md4>();
+    }
+
+    #[test]
+    fn forge_md5() {
+        forge_works::<Md5>();
+    }
+}
@@ -0,0 +1,11 @@
+/// This module contains generic attacks, reusable across challenges.
+pub mod bitflip;
+pub mod cbc;
+pub mod ctr;
+pub mod dh_mitm;
+pub mod ecb;
+pub mod length_extension;
+pub mod padding_oracle;
+pub mod square;
+pub mod srp_dictionary;
+pub mod timing;
@@ -0,0 +1,167 @@
+//! The CBC padding oracle attack.
+//!
+//! Given an oracle telling whether a ciphertext decrypts to correctly padded
+//! plaintext, any CBC ciphertext can be decrypted one byte at a time, by tweaking
+//! the preceding block until the target byte decrypts to a valid padding byte.
+
+use crate::{
+    oracle::{PaddingOracle, PaddingQuery},
+    Error, Result,
+};
+
+/// Decrypts a single block of CBC ciphertext.
+///
+/// `prev` is the block preceding `block` in the ciphertext (or the IV, for the first block).
+/// `oracle` takes an IV and a ciphertext, and returns whether its padding is valid.
+///
+/// Returns the plaintext block.
+pub fn decrypt_block<O: PaddingOracle>(oracle: &O, prev: &[u8], block: &[u8]) -> Result<Vec<u8>> {
+    let bs = block.len();
+
+    if prev.len() != bs {
+        return Err(Error::InvalidIvLength(prev.len()));
+    }
+
+    // The block cipher's output for `block`, before XORing with `prev`
+    let mut intermediate = vec![0u8; bs];
+
+    // The IV we forge to control the decrypted plaintext
+    let mut forged = vec![0u8; bs];
+
+    for pos in (0..bs).rev() {
+        let pad = (bs - pos) as u8;
+
+        // Make the bytes we already know decrypt to the expected padding
+        for i in pos + 1..bs {
+            forged[i] = intermediate[i] ^ pad;
+        }
+
+        let mut guess = None;
+
+        for g in 0..=255 {
+            forged[pos] = g;
+
+            if !oracle.query(PaddingQuery {
+                iv: &forged,
+                ciphertext: block,
+            })? {
+                continue;
+            }
+
+            // When attacking the last byte, the padding might be valid by accident:
+            // for example, the plaintext could end in `\x02\x02` rather than `\x01`.
+            // Tweaking the previous byte rules this out, since `\x01` does not care.
+            if pos == bs - 1 && pos > 0 {
+                let mut check = forged.clone();
+                check[pos - 1] ^= 0xff;
+
+                if !oracle.query(PaddingQuery {
+                    iv: &check,
+                    ciphertext: block,
+                })? {
+                    continue;
+                }
+            }
+
+            guess = Some(g);
+            break;
+        }
+
+        match guess {
+            Some(g) => intermediate[pos] = g ^ pad,
+            None => return Err(Error::AttackFailed("No byte produced a valid padding")),
+        }
+    }
+
+    Ok(intermediate.iter().zip(prev).map(|(i, p)| i ^ p).collect())
+}
+
+/// Decrypts a CBC ciphertext using a padding oracle.
+///
+/// `oracle` takes an IV and a ciphertext, and returns whether its padding is valid.
+///
+/// Returns the plaintext, with its padding still attached.
+pub fn decrypt<O: PaddingOracle>(oracle: &O, iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    let bs = iv.len();
+
+    // `usize::is_multiple_of` needs Rust 1.87, which is too recent for this crate
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    if bs == 0 || ciphertext.len() % bs != 0 {
+        return Err(Error::InvalidInputLength(ciphertext.len()));
+    }
+
+    let mut plaintext = Vec::with_capacity(ciphertext.len());
+    let mut prev = iv;
+
+    for block in ciphertext.chunks(bs) {
+        plaintext.extend(decrypt_block(oracle, prev, block)?);
+        prev = block;
+    }
+
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::{aes::cbc, misc},
+        oracle::{FnOracle, Oracle},
+    };
+
+    /// Builds a padding oracle for the given key.
+    fn padding_oracle(key: &[u8]) -> impl PaddingOracle + '_ {
+        FnOracle::new(move |q: PaddingQuery| {
+            match cbc::decrypt(q.ciphertext, key, Some(q.iv), true) {
+                Ok(_) => Ok(true),
+                Err(Error::Padding(_)) => Ok(false),
+                Err(e) => Err(e),
+            }
+        })
+    }
+
+    #[test]
+    fn decrypt_works() {
+        let key = b"YELLOW SUBMARINE";
+        let iv = b"0123456789abcdef";
+
+        let oracle = padding_oracle(key);
+
+        for pt in vec![
+            &b""[..],
+            &b"A"[..],
+            &b"YELLOW SUBMARINE"[..],
+            &b"We all live in a yellow submarine"[..],
+        ]
+        .into_iter()
+        {
+            let ct = cbc::encrypt(pt, key, Some(iv)).unwrap();
+            assert_eq!(decrypt(&oracle, iv, &ct).unwrap(), misc::pkcs7(pt, 16));
+        }
+
+        // Each byte takes at most 256 queries, plus one to rule out a false positive
+        assert!(oracle.queries() <= (16 + 16 + 32 + 48) * 257);
+    }
+
+    #[test]
+    fn decrypt_block_handles_false_positive() {
+        let key = b"YELLOW SUBMARINE";
+        let oracle = padding_oracle(key);
+
+        // Decrypting with a zero IV, guessing 0x01 for the last byte produces
+        // "\x02\x02", which is valid padding, before the real "\x01" is tried.
+        let pt = b"AAAAAAAAAAAAAA\x02\x03";
+        let ct = cbc::encrypt(pt, key, None).unwrap();
+
+        assert_eq!(decrypt_block(&oracle, &[0; 16], &ct[..16]).unwrap(), pt);
+    }
+
+    #[test]
+    fn decrypt_fails_on_oracle_error() {
+        // The oracle fails because it uses an invalid key
+        let oracle = padding_oracle(b"YELLOW");
+
+        assert!(decrypt(&oracle, &[0; 16], &[0; 16]).is_err());
+        assert_eq!(oracle.queries(), 1);
+    }
+}
@@ -0,0 +1,100 @@
+//! The integral (or square) attack against reduced-round AES.
+//!
+//! A Λ-set is a set of 256 plaintexts which differ in a single byte, taking every
+//! possible value, and are equal everywhere else. After three AES rounds, every
+//! byte of the state XORs to zero over a Λ-set ("balanced"). When attacking four
+//! rounds, the last one can therefore be peeled off one key byte at a time: the
+//! correct key byte is the one that makes the partially decrypted bytes balanced.
+
+use crate::{
+    crypto::aes::{
+        soft::{self, State, INV_SBOX},
+        BLOCK_SIZE,
+    },
+    oracle::EncryptionOracle,
+    Error, Result,
+};
+
+/// Maximum number of Λ-sets to try before giving up.
+const MAX_LAMBDA_SETS: usize = 16;
+
+/// Recovers the last round key of 4-round AES.
+///
+/// `oracle` must encrypt a single block under the unknown key, without padding.
+pub fn recover_last_round_key<O: EncryptionOracle>(oracle: &O) -> Result<State> {
+    // Start with every key byte as a candidate, for every position
+    let mut candidates = vec![(0..=255).collect::<Vec<u8>>(); BLOCK_SIZE];
+
+    for _ in 0..MAX_LAMBDA_SETS {
+        // Build a Λ-set with a random constant part, and encrypt it
+        let mut plaintext: State = rand::random();
+
+        let ciphertexts = (0..=255)
+            .map(|b| {
+                plaintext[0] = b;
+                oracle.query(&plaintext)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if let Some(ct) = ciphertexts.iter().find(|ct| ct.len() != BLOCK_SIZE) {
+            return Err(Error::InvalidInputLength(ct.len()));
+        }
+
+        // Only keep the guesses which produce a balanced byte
+        for (pos, guesses) in candidates.iter_mut().enumerate() {
+            guesses.retain(|&k| {
+                ciphertexts
+                    .iter()
+                    .fold(0, |acc, ct| acc ^ INV_SBOX[(ct[pos] ^ k) as usize])
+                    == 0
+            });
+        }
+
+        if candidates.iter().any(|guesses| guesses.is_empty()) {
+            return Err(Error::AttackFailed("No key byte produces a balanced state"));
+        }
+
+        if candidates.iter().all(|guesses| guesses.len() == 1) {
+            let mut key = [0; BLOCK_SIZE];
+            for (k, guesses) in key.iter_mut().zip(candidates) {
+                *k = guesses[0];
+            }
+            return Ok(key);
+        }
+    }
+
+    Err(Error::AttackFailed("Too many Λ-sets without a unique key"))
+}
+
+/// Recovers the AES-128 key used by a 4-round AES encryption oracle.
+pub fn recover_key<O: EncryptionOracle>(oracle: &O) -> Result<State> {
+    Ok(soft::invert_key_schedule(
+        &recover_last_round_key(oracle)?,
+        4,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::aes::{ecb, soft::AesCore},
+        oracle::FnOracle,
+    };
+
+    #[test]
+    fn recover_4_round_aes_key() {
+        let key: [u8; 16] = rand::random();
+        let cipher = AesCore::with_rounds(key, 4).unwrap();
+
+        // Two Λ-sets are usually enough to get rid of false positives, four almost surely
+        let oracle =
+            || FnOracle::new(|i: &[u8]| ecb::encrypt_with(&cipher, i, false)).with_budget(4 * 256);
+
+        assert_eq!(
+            recover_last_round_key(&oracle()).unwrap(),
+            cipher.round_keys()[4]
+        );
+        assert_eq!(recover_key(&oracle()).unwrap(), key);
+    }
+}
@@ -0,0 +1,112 @@
+//! Offline dictionary attack against simplified SRP.
+//!
+//! The attacker poses as the server and picks `b = 1` and `u = 1`, so that the client's
+//! secret becomes `S = g^(a + x) = A * g^x mod N`. The captured proof can then be
+//! checked against every candidate password without talking to anyone.
+
+use crate::{
+    crypto::bigint::{self, BigUint},
+    network::Endpoint,
+    protocols::srp::{self, Message, SrpParams},
+    Error, Result,
+};
+
+use num_traits::One;
+use std::{fs, path::Path};
+
+/// What the attacker captures from a login attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transcript {
+    /// The client's identity.
+    pub email: String,
+    /// The client's public key `A`.
+    pub public: BigUint,
+    /// The salt sent to the client.
+    pub salt: Vec<u8>,
+    /// The proof computed by the client.
+    pub proof: Vec<u8>,
+}
+
+/// Poses as the server, with `b = 1`, `B = g` and `u = 1`, and captures the client's proof.
+///
+/// The client is then told that the login failed, as if it had mistyped its password.
+pub fn impersonate_server(ep: Endpoint<Message>, params: &SrpParams) -> Result<Transcript> {
+    let (email, public) = match ep.recv()? {
+        Message::Hello { email, public } => (email, public),
+        _ => return Err(Error::Protocol("Expected a hello")),
+    };
+
+    let salt = rand::random::<[u8; 16]>().to_vec();
+
+    ep.send(Message::Challenge {
+        salt: salt.clone(),
+        public: params.group.g.clone(),
+        u: Some(BigUint::one()),
+    })?;
+
+    let proof = match ep.recv()? {
+        Message::Proof(proof) => proof,
+        _ => return Err(Error::Protocol("Expected a proof")),
+    };
+
+    ep.send(Message::Result(false))?;
+
+    Ok(Transcript {
+        email,
+        public,
+        salt,
+        proof,
+    })
+}
+
+/// Recovers the client's password from a captured transcript,
+/// trying every line of the word list at `wordlist`.
+pub fn crack_password<P: AsRef<Path>>(
+    transcript: &Transcript,
+    params: &SrpParams,
+    wordlist: P,
+) -> Result<String> {
+    let n = &params.group.p;
+
+    fs::read_to_string(wordlist)?
+        .lines()
+        .map(str::trim_end)
+        .filter(|word| !word.is_empty())
+        .find(|word| {
+            // S = A * g^x mod N
+            let x = srp::private_key(&transcript.salt, word.as_bytes());
+            let secret = &transcript.public * bigint::modexp(&params.group.g, &x, n) % n;
+
+            srp::proof(&srp::session_key(&secret), &transcript.salt) == transcript.proof
+        })
+        .map(str::to_owned)
+        .ok_or(Error::AttackFailed("Password not in word list"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network;
+
+    #[test]
+    fn crack_password_works() {
+        let client = srp::Client::new(SrpParams::simplified(), "alice@example.com", "sunshine");
+
+        let (client, transcript) = network::run(
+            move |ep| srp::run_client(ep, client),
+            |ep| impersonate_server(ep, &SrpParams::simplified()),
+        );
+        let transcript = transcript.unwrap();
+
+        assert!(!client.unwrap());
+        assert_eq!(
+            crack_password(&transcript, &SrpParams::simplified(), "data/38.txt").unwrap(),
+            "sunshine"
+        );
+        assert_eq!(
+            crack_password(&transcript, &SrpParams::simplified(), "data/7.txt").unwrap_err(),
+            Error::AttackFailed("Password not in word list")
+        );
+        assert!(crack_password(&transcript, &SrpParams::simplified(), "data/none.txt").is_err());
+    }
+}
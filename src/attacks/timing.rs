@@ -0,0 +1,78 @@
+//! Timing attacks against early-exit comparisons.
+
+use crate::{oracle::TimingOracle, Error, Result};
+
+use std::time::Duration;
+
+/// Recovers a valid MAC of `mac_len` bytes from an oracle leaking
+/// the length of the correct prefix through its response time.
+///
+/// Each byte is guessed by querying every candidate `samples` times
+/// and picking the one with the largest median response time. The last byte
+/// does not leak any timing, but the oracle tells us when the MAC is valid.
+pub fn recover_mac<O: TimingOracle>(oracle: &O, mac_len: usize, samples: usize) -> Result<Vec<u8>> {
+    if mac_len == 0 || samples == 0 {
+        return Err(Error::InvalidArgument(
+            "MAC length and sample count must be positive",
+        ));
+    }
+
+    let mut mac = vec![0; mac_len];
+
+    for i in 0..mac_len - 1 {
+        let mut best = (0, Duration::from_secs(0));
+
+        for b in 0..=255 {
+            mac[i] = b;
+
+            let mut times = (0..samples)
+                .map(|_| oracle.query(&mac).map(|(_, t)| t))
+                .collect::<Result<Vec<_>>>()?;
+            times.sort();
+
+            let median = times[samples / 2];
+            if median > best.1 {
+                best = (b, median);
+            }
+        }
+
+        mac[i] = best.0;
+    }
+
+    for b in 0..=255 {
+        mac[mac_len - 1] = b;
+
+        if oracle.query(&mac)?.0 {
+            return Ok(mac);
+        }
+    }
+
+    Err(Error::AttackFailed("No valid MAC found"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::FnOracle;
+
+    use rand::Rng;
+
+    #[test]
+    fn recover_mac_works() {
+        let secret = rand::random::<[u8; 8]>();
+
+        // Simulate a comparison taking 10µs for each matching byte, without actually
+        // waiting. The noise stays below the per-byte delay, so the test is not flaky.
+        let oracle = FnOracle::new(|mac: &[u8]| {
+            let matching = mac.iter().zip(&secret).take_while(|(a, b)| a == b).count();
+            let noise = rand::thread_rng().gen_range(0, 10);
+
+            Ok((
+                mac == secret,
+                Duration::from_micros(10 * matching as u64 + noise),
+            ))
+        });
+
+        assert_eq!(recover_mac(&oracle, 8, 5).unwrap(), secret);
+    }
+}
@@ -0,0 +1,1029 @@
+use crate::{
+    crypto::{cipher::BlockCipher, prng::Mt19937_64},
+    utils, Error, Result,
+};
+
+use rand::{distributions::Standard, random, thread_rng, Rng};
+
+pub mod soft;
+
+/// Size of an AES block, in bytes.
+pub const BLOCK_SIZE: usize = 16;
+
+/// Block encryption backed by OpenSSL.
+#[cfg(feature = "openssl")]
+mod backend {
+    use super::BLOCK_SIZE;
+
+    use openssl::symm::{self, Cipher};
+
+    #[derive(Debug, Clone)]
+    pub struct Backend {
+        key: Vec<u8>,
+    }
+
+    impl Backend {
+        pub fn new(key: &[u8]) -> Self {
+            Backend { key: key.to_vec() }
+        }
+
+        pub fn encrypt_block(&self, block: &mut [u8]) {
+            self.process(symm::Mode::Encrypt, block)
+        }
+
+        pub fn decrypt_block(&self, block: &mut [u8]) {
+            self.process(symm::Mode::Decrypt, block)
+        }
+
+        fn process(&self, mode: symm::Mode, block: &mut [u8]) {
+            assert_eq!(block.len(), BLOCK_SIZE, "Invalid block size");
+
+            let cipher = match self.key.len() {
+                16 => Cipher::aes_128_ecb(),
+                24 => Cipher::aes_192_ecb(),
+                _ => Cipher::aes_256_ecb(),
+            };
+
+            let mut output = [0; 2 * BLOCK_SIZE];
+            let mut c = symm::Crypter::new(cipher, mode, &self.key, None).expect("Invalid AES key");
+
+            c.pad(false);
+
+            let written = c
+                .update(block, &mut output)
+                .expect("AES block operation failed");
+            assert_eq!(written, BLOCK_SIZE);
+
+            block.copy_from_slice(&output[..BLOCK_SIZE]);
+        }
+    }
+}
+
+/// Block encryption backed by the pure-Rust implementation in [`soft`](../soft/index.html).
+#[cfg(not(feature = "openssl"))]
+mod backend {
+    use super::soft::AesCore;
+    use crate::crypto::cipher::BlockCipher;
+
+    #[derive(Debug, Clone)]
+    pub struct Backend {
+        core: AesCore,
+    }
+
+    impl Backend {
+        pub fn new(key: &[u8]) -> Self {
+            Backend {
+                core: AesCore::new(key).expect("Invalid AES key"),
+            }
+        }
+
+        pub fn encrypt_block(&self, block: &mut [u8]) {
+            self.core.encrypt_block(block)
+        }
+
+        pub fn decrypt_block(&self, block: &mut [u8]) {
+            self.core.decrypt_block(block)
+        }
+    }
+}
+
+/// AES with a 128-bit key.
+#[derive(Debug, Clone)]
+pub struct Aes128 {
+    inner: backend::Backend,
+}
+
+impl Aes128 {
+    pub fn new(key: [u8; 16]) -> Self {
+        Aes128 {
+            inner: backend::Backend::new(&key),
+        }
+    }
+}
+
+impl BlockCipher for Aes128 {
+    fn block_size(&self) -> usize {
+        BLOCK_SIZE
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        self.inner.encrypt_block(block)
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        self.inner.decrypt_block(block)
+    }
+}
+
+/// AES with a 192-bit key.
+#[derive(Debug, Clone)]
+pub struct Aes192 {
+    inner: backend::Backend,
+}
+
+impl Aes192 {
+    pub fn new(key: [u8; 24]) -> Self {
+        Aes192 {
+            inner: backend::Backend::new(&key),
+        }
+    }
+}
+
+impl BlockCipher for Aes192 {
+    fn block_size(&self) -> usize {
+        BLOCK_SIZE
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        self.inner.encrypt_block(block)
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        self.inner.decrypt_block(block)
+    }
+}
+
+/// AES with a 256-bit key.
+#[derive(Debug, Clone)]
+pub struct Aes256 {
+    inner: backend::Backend,
+}
+
+impl Aes256 {
+    pub fn new(key: [u8; 32]) -> Self {
+        Aes256 {
+            inner: backend::Backend::new(&key),
+        }
+    }
+}
+
+impl BlockCipher for Aes256 {
+    fn block_size(&self) -> usize {
+        BLOCK_SIZE
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        self.inner.encrypt_block(block)
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        self.inner.decrypt_block(block)
+    }
+}
+
+/// AES with any of the supported key sizes, selected by key length.
+#[derive(Debug, Clone)]
+pub enum Aes {
+    Aes128(Aes128),
+    Aes192(Aes192),
+    Aes256(Aes256),
+}
+
+impl Aes {
+    /// Creates an AES cipher for the given key.
+    ///
+    /// Returns an error if the key is not 16, 24 or 32 bytes long.
+    pub fn new<K: AsRef<[u8]>>(key: K) -> Result<Self> {
+        let key = key.as_ref();
+
+        let mut k128 = [0; 16];
+        let mut k192 = [0; 24];
+        let mut k256 = [0; 32];
+
+        Ok(match key.len() {
+            16 => {
+                k128.copy_from_slice(key);
+                Aes::Aes128(Aes128::new(k128))
+            }
+            24 => {
+                k192.copy_from_slice(key);
+                Aes::Aes192(Aes192::new(k192))
+            }
+            32 => {
+                k256.copy_from_slice(key);
+                Aes::Aes256(Aes256::new(k256))
+            }
+            n => return Err(Error::InvalidKeyLength(n)),
+        })
+    }
+
+    fn inner(&self) -> &dyn BlockCipher {
+        match self {
+            Aes::Aes128(c) => c,
+            Aes::Aes192(c) => c,
+            Aes::Aes256(c) => c,
+        }
+    }
+}
+
+impl BlockCipher for Aes {
+    fn block_size(&self) -> usize {
+        BLOCK_SIZE
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        self.inner().encrypt_block(block)
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        self.inner().decrypt_block(block)
+    }
+}
+
+pub mod ecb {
+    use super::{Aes, Error, Result};
+    use crate::crypto::{cipher::BlockCipher, misc};
+
+    /// Encrypts the input in ECB mode using the given block cipher.
+    ///
+    /// If `pad` is false, the input must be a multiple of the block size.
+    pub fn encrypt_with<C, I>(cipher: &C, input: I, pad: bool) -> Result<Vec<u8>>
+    where
+        C: BlockCipher + ?Sized,
+        I: AsRef<[u8]>,
+    {
+        let bs = cipher.block_size();
+
+        let mut output = if pad {
+            misc::pkcs7(input, bs as u8)
+        } else {
+            input.as_ref().to_vec()
+        };
+
+        if output.len() % bs != 0 {
+            return Err(Error::InvalidInputLength(output.len()));
+        }
+
+        for block in output.chunks_mut(bs) {
+            cipher.encrypt_block(block);
+        }
+
+        Ok(output)
+    }
+
+    /// Decrypts the input in ECB mode using the given block cipher.
+    ///
+    /// If `pad` is true, the PKCS#7 padding is validated and stripped from the plaintext.
+    pub fn decrypt_with<C, I>(cipher: &C, input: I, pad: bool) -> Result<Vec<u8>>
+    where
+        C: BlockCipher + ?Sized,
+        I: AsRef<[u8]>,
+    {
+        let bs = cipher.block_size();
+        let mut output = input.as_ref().to_vec();
+
+        if output.len() % bs != 0 {
+            return Err(Error::InvalidInputLength(output.len()));
+        }
+
+        for block in output.chunks_mut(bs) {
+            cipher.decrypt_block(block);
+        }
+
+        if pad {
+            Ok(misc::pkcs7_unpad(output, bs as u8)?)
+        } else {
+            Ok(output)
+        }
+    }
+
+    /// Encrypts the input using AES-ECB, with the key size selected by the key length.
+    pub fn encrypt<I, K>(input: I, key: K, pad: bool) -> Result<Vec<u8>>
+    where
+        I: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+    {
+        encrypt_with(&Aes::new(key)?, input, pad)
+    }
+
+    /// Decrypts the input using AES-ECB, with the key size selected by the key length.
+    pub fn decrypt<I, K>(input: I, key: K, pad: bool) -> Result<Vec<u8>>
+    where
+        I: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+    {
+        decrypt_with(&Aes::new(key)?, input, pad)
+    }
+}
+
+pub mod cbc {
+    use super::{ecb, Aes, Error, Result};
+    use crate::crypto::{cipher::BlockCipher, misc};
+
+    /// Encrypts the input in CBC mode using the given block cipher,
+    /// after applying PKCS#7 padding.
+    ///
+    /// If no IV is specified, an all-zero IV is used.
+    pub fn encrypt_with<C, I>(cipher: &C, input: I, iv: Option<&[u8]>) -> Result<Vec<u8>>
+    where
+        C: BlockCipher + ?Sized,
+        I: AsRef<[u8]>,
+    {
+        let n = cipher.block_size();
+
+        let input = misc::pkcs7(input.as_ref(), n as u8);
+        let iv = iv.map(|iv| iv.to_owned()).unwrap_or_else(|| vec![0; n]);
+
+        if iv.len() != n {
+            return Err(Error::InvalidIvLength(iv.len()));
+        }
+
+        let mut ciphertext = Vec::with_capacity(input.len());
+
+        for (i, block) in input.chunks(n).enumerate() {
+            let chunk = misc::xor(
+                block,
+                if i == 0 {
+                    iv.as_ref()
+                } else {
+                    &ciphertext[(i - 1) * n..i * n]
+                },
+            );
+
+            ciphertext.extend(ecb::encrypt_with(cipher, chunk, false)?);
+        }
+
+        Ok(ciphertext)
+    }
+
+    /// Decrypts the input in CBC mode using the given block cipher.
+    ///
+    /// If `pad` is true, the PKCS#7 padding is validated and stripped from the plaintext.
+    pub fn decrypt_with<C, I>(cipher: &C, input: I, iv: Option<&[u8]>, pad: bool) -> Result<Vec<u8>>
+    where
+        C: BlockCipher + ?Sized,
+        I: AsRef<[u8]>,
+    {
+        let input = input.as_ref();
+        let n = cipher.block_size();
+
+        let iv = iv.map(|iv| iv.to_owned()).unwrap_or_else(|| vec![0; n]);
+
+        if iv.len() != n {
+            return Err(Error::InvalidIvLength(iv.len()));
+        }
+
+        let mut plaintext = ecb::decrypt_with(cipher, input, false)?;
+
+        for (i, block) in plaintext.chunks_mut(n).enumerate() {
+            block.copy_from_slice(&misc::xor(
+                &*block,
+                if i == 0 {
+                    iv.as_ref()
+                } else {
+                    &input[(i - 1) * n..i * n]
+                },
+            ));
+        }
+
+        if pad {
+            Ok(misc::pkcs7_unpad(plaintext, n as u8)?)
+        } else {
+            Ok(plaintext)
+        }
+    }
+
+    /// Encrypts the input using AES-CBC, after applying PKCS#7 padding.
+    pub fn encrypt<I, K>(input: I, key: K, iv: Option<&[u8]>) -> Result<Vec<u8>>
+    where
+        I: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+    {
+        encrypt_with(&Aes::new(key)?, input, iv)
+    }
+
+    /// Decrypts the input using AES-CBC.
+    ///
+    /// If `pad` is true, the PKCS#7 padding is validated and stripped from the plaintext.
+    pub fn decrypt<I, K>(input: I, key: K, iv: Option<&[u8]>, pad: bool) -> Result<Vec<u8>>
+    where
+        I: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+    {
+        decrypt_with(&Aes::new(key)?, input, iv, pad)
+    }
+}
+
+pub mod ctr {
+    use super::{ecb, Aes, Error, Result};
+    use crate::crypto::cipher::BlockCipher;
+
+    /// Byte order used to encode the counter within a keystream block.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Endianness {
+        Little,
+        Big,
+    }
+
+    /// Describes how the nonce and the counter are laid out in a keystream block.
+    ///
+    /// The nonce always comes first, immediately followed by the counter.
+    /// Their lengths (in bytes) must add up to the cipher's block size.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Layout {
+        pub nonce_len: usize,
+        pub counter_len: usize,
+        pub endianness: Endianness,
+    }
+
+    impl Layout {
+        /// The layout used by Cryptopals: 64-bit nonce, 64-bit little-endian counter.
+        pub const CRYPTOPALS: Layout = Layout {
+            nonce_len: 8,
+            counter_len: 8,
+            endianness: Endianness::Little,
+        };
+
+        /// The layout suggested by NIST SP 800-38A: 64-bit nonce, 64-bit big-endian counter.
+        pub const NIST: Layout = Layout {
+            nonce_len: 8,
+            counter_len: 8,
+            endianness: Endianness::Big,
+        };
+
+        /// Encodes the counter block for the given nonce and counter value.
+        ///
+        /// The counter wraps around when it overflows its width.
+        fn encode(&self, nonce: &[u8], counter: u128) -> Vec<u8> {
+            let mut block = nonce.to_vec();
+
+            match self.endianness {
+                Endianness::Little => {
+                    block.extend_from_slice(&counter.to_le_bytes()[..self.counter_len])
+                }
+                Endianness::Big => {
+                    block.extend_from_slice(&counter.to_be_bytes()[16 - self.counter_len..])
+                }
+            }
+
+            block
+        }
+    }
+
+    impl Default for Layout {
+        fn default() -> Self {
+            Layout::CRYPTOPALS
+        }
+    }
+
+    /// A block cipher in counter mode, with support for random access to the keystream.
+    #[derive(Debug, Clone)]
+    pub struct Ctr<C = Aes> {
+        cipher: C,
+        nonce: Vec<u8>,
+        layout: Layout,
+        counter: u128,
+        offset: u64,
+    }
+
+    impl Ctr {
+        /// Creates a new AES-CTR instance positioned at the start of the keystream.
+        ///
+        /// Returns an error if the key or the nonce are invalid,
+        /// or if the layout does not fill exactly one block.
+        pub fn new<K, N>(key: K, nonce: N, layout: Layout) -> Result<Self>
+        where
+            K: AsRef<[u8]>,
+            N: AsRef<[u8]>,
+        {
+            Ctr::with_cipher(Aes::new(key)?, nonce, layout)
+        }
+    }
+
+    impl<C: BlockCipher> Ctr<C> {
+        /// Creates a new CTR instance over the given block cipher.
+        ///
+        /// Returns an error if the nonce does not match the layout,
+        /// or if the layout does not fill exactly one block.
+        pub fn with_cipher<N: AsRef<[u8]>>(cipher: C, nonce: N, layout: Layout) -> Result<Self> {
+            let nonce = nonce.as_ref();
+
+            if layout.nonce_len + layout.counter_len != cipher.block_size()
+                || layout.counter_len == 0
+                || layout.counter_len > 16
+            {
+                return Err(Error::InvalidArgument("Invalid CTR layout"));
+            }
+            if nonce.len() != layout.nonce_len {
+                return Err(Error::InvalidIvLength(nonce.len()));
+            }
+
+            Ok(Ctr {
+                cipher,
+                nonce: nonce.to_vec(),
+                layout,
+                counter: 0,
+                offset: 0,
+            })
+        }
+
+        /// Sets the value of the counter for the first keystream block.
+        pub fn with_initial_counter(mut self, counter: u128) -> Self {
+            self.counter = counter;
+            self
+        }
+
+        /// Returns the current position in the keystream, in bytes.
+        pub fn position(&self) -> u64 {
+            self.offset
+        }
+
+        /// Moves to an arbitrary byte offset in the keystream.
+        pub fn seek(&mut self, offset: u64) {
+            self.offset = offset;
+        }
+
+        /// XORs the keystream into `data`, starting from the current position.
+        ///
+        /// The position is advanced by the length of `data`.
+        pub fn apply_keystream(&mut self, data: &mut [u8]) -> Result<()> {
+            let bs = self.cipher.block_size() as u64;
+            let mut done = 0;
+
+            while done < data.len() {
+                let skip = (self.offset % bs) as usize;
+                let n = usize::min(bs as usize - skip, data.len() - done);
+
+                let keystream = self.block(self.offset / bs)?;
+                for (d, k) in data[done..done + n].iter_mut().zip(&keystream[skip..]) {
+                    *d ^= k;
+                }
+
+                done += n;
+                self.offset += n as u64;
+            }
+
+            Ok(())
+        }
+
+        /// Processes `input` from the current position, returning the result.
+        pub fn process<I: AsRef<[u8]>>(&mut self, input: I) -> Result<Vec<u8>> {
+            let mut output = input.as_ref().to_vec();
+            self.apply_keystream(&mut output)?;
+            Ok(output)
+        }
+
+        /// Replaces the plaintext of `ciphertext` at `offset` with `newtext`,
+        /// re-encrypting only the affected bytes.
+        ///
+        /// The ciphertext is extended if `newtext` goes past its end.
+        /// The position in the keystream is left right after the edited window.
+        pub fn edit<I, T>(&mut self, ciphertext: I, offset: usize, newtext: T) -> Result<Vec<u8>>
+        where
+            I: AsRef<[u8]>,
+            T: AsRef<[u8]>,
+        {
+            let mut ciphertext = ciphertext.as_ref().to_vec();
+            let newtext = newtext.as_ref();
+
+            if offset > ciphertext.len() {
+                return Err(Error::InvalidArgument("Edit offset past end of ciphertext"));
+            }
+
+            self.seek(offset as u64);
+            let window = self.process(newtext)?;
+
+            let end = usize::min(ciphertext.len(), offset + window.len());
+            ciphertext.splice(offset..end, window);
+
+            Ok(ciphertext)
+        }
+
+        /// Computes the `index`-th block of keystream.
+        fn block(&self, index: u64) -> Result<Vec<u8>> {
+            let counter = self.counter.wrapping_add(u128::from(index));
+            ecb::encrypt_with(
+                &self.cipher,
+                self.layout.encode(&self.nonce, counter),
+                false,
+            )
+        }
+    }
+
+    /// Encrypts the input using AES-CTR with the Cryptopals nonce/counter layout.
+    pub fn encrypt<I, K>(input: I, key: K, nonce: u64) -> Result<Vec<u8>>
+    where
+        I: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+    {
+        Ctr::new(key, nonce.to_le_bytes(), Layout::CRYPTOPALS)?.process(input)
+    }
+
+    /// Edits a ciphertext produced by [`encrypt`](fn.encrypt.html) in place,
+    /// replacing the plaintext at `offset` with `newtext`.
+    pub fn edit<I, K, T>(
+        ciphertext: I,
+        key: K,
+        nonce: u64,
+        offset: usize,
+        newtext: T,
+    ) -> Result<Vec<u8>>
+    where
+        I: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+        T: AsRef<[u8]>,
+    {
+        Ctr::new(key, nonce.to_le_bytes(), Layout::CRYPTOPALS)?.edit(ciphertext, offset, newtext)
+    }
+
+    /// Decrypts the input using AES-CTR with the Cryptopals nonce/counter layout.
+    pub fn decrypt<I, K>(input: I, key: K, nonce: u64) -> Result<Vec<u8>>
+    where
+        I: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+    {
+        encrypt(input, key, nonce)
+    }
+}
+
+/// Generates a random AES-128 key.
+pub fn random_key() -> [u8; 16] {
+    rand::random()
+}
+
+/// Generates a random AES-128 key from the specified seed.
+pub fn seeded_key(seed: u64) -> [u8; 16] {
+    let mut rng = Mt19937_64::new(seed);
+    rng.gen()
+}
+
+/// Generates a random number of bytes in the range `[min, max]`.
+fn random_bytes(min: usize, max: usize) -> Vec<u8> {
+    thread_rng()
+        .sample_iter::<u8, _>(Standard)
+        .take(thread_rng().gen_range(min, max + 1))
+        .collect()
+}
+
+/// Encrypts data with AES-128 using random parameters.
+///
+/// The data is first prefixed and suffixed with 5-10 random bytes each.
+/// It is then encrypted with a randomly chosen key, using either ECB
+/// or CBC (with random IVs) with equal probability.
+///
+/// Returns whether ECB was used, and the encrypted data.
+pub fn encrypt_random<I: AsRef<[u8]>>(input: I) -> Result<(bool, Vec<u8>)> {
+    let mut input = input.as_ref().to_vec();
+
+    // Generate random encryption key and IVs
+    let key = random_key();
+    let iv = random_key();
+
+    // Add variable-length random prefix and suffix to the plaintext
+    input.splice(0..0, random_bytes(5, 10));
+    input.extend(random_bytes(5, 10));
+
+    // Encrypt half the time using ECB and half the time using CBC
+    let res = if random::<bool>() {
+        (true, ecb::encrypt(input, key, true)?)
+    } else {
+        (false, cbc::encrypt(input, key, Some(&iv))?)
+    };
+
+    Ok(res)
+}
+
+/// Encrypts data with AES-128-ECB using a random key generated from the seed.
+///
+/// The input is affixed with a fixed, unknown string before encryption.
+pub fn encrypt_seeded<I: AsRef<[u8]>>(input: I, seed: u64) -> Result<Vec<u8>> {
+    let mut input = input.as_ref().to_vec();
+
+    // Trailing unknown text that we need to decrypt
+    let suffix = utils::from_base64(
+        "Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkg\
+         aGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBq\
+         dXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUg\
+         YnkK",
+    )?;
+
+    // Generate encryption key from seed
+    let key = seeded_key(seed);
+
+    // Append suffix to the input
+    input.extend(suffix);
+
+    // Encrypt using ECB
+    ecb::encrypt(input, key, true)
+}
+
+/// Encrypts data with AES-128-ECB using a random key generated from the seed.
+///
+/// The input is prefixed with a random count of random bytes, also generated
+/// from the seed, and affixed with the same unknown string as [`encrypt_seeded`].
+///
+/// [`encrypt_seeded`]: fn.encrypt_seeded.html
+pub fn encrypt_seeded_prefixed<I: AsRef<[u8]>>(input: I, seed: u64) -> Result<Vec<u8>> {
+    // Use a different seed for the prefix, so that it is unrelated to the key
+    let mut rng = Mt19937_64::new(!seed);
+
+    let len = rng.gen_range(1, 65);
+    let prefix = rng
+        .sample_iter::<u8, _>(Standard)
+        .take(len)
+        .collect::<Vec<_>>();
+
+    encrypt_seeded([&prefix, input.as_ref()].concat(), seed)
+}
+
+/// Encrypts the comment string for the user data with AES-128-CBC,
+/// using a random key generated from the seed.
+pub fn encrypt_comment_cbc<I: AsRef<str>>(userdata: I, seed: u64) -> Result<Vec<u8>> {
+    cbc::encrypt(utils::comment_for(userdata), seeded_key(seed), None)
+}
+
+/// Decrypts a comment string encrypted by [`encrypt_comment_cbc`],
+/// and returns whether it grants admin privileges.
+///
+/// [`encrypt_comment_cbc`]: fn.encrypt_comment_cbc.html
+pub fn is_admin_cbc<I: AsRef<[u8]>>(ciphertext: I, seed: u64) -> Result<bool> {
+    Ok(utils::is_admin(cbc::decrypt(
+        ciphertext,
+        seeded_key(seed),
+        None,
+        true,
+    )?))
+}
+
+/// Encrypts the comment string for the user data with AES-128-CBC,
+/// using a random key generated from the seed both as the key and as the IV.
+pub fn encrypt_comment_cbc_iv_key<I: AsRef<str>>(userdata: I, seed: u64) -> Result<Vec<u8>> {
+    let key = seeded_key(seed);
+    cbc::encrypt(utils::comment_for(userdata), key, Some(&key))
+}
+
+/// Decrypts a comment string encrypted by [`encrypt_comment_cbc_iv_key`],
+/// and returns whether it grants admin privileges.
+///
+/// Returns an [`Error::InvalidPlaintext`] error, leaking the whole plaintext,
+/// if the decrypted comment contains any non-ASCII byte.
+///
+/// [`encrypt_comment_cbc_iv_key`]: fn.encrypt_comment_cbc_iv_key.html
+/// [`Error::InvalidPlaintext`]: ../../enum.Error.html#variant.InvalidPlaintext
+pub fn is_admin_cbc_iv_key<I: AsRef<[u8]>>(ciphertext: I, seed: u64) -> Result<bool> {
+    let key = seeded_key(seed);
+    let plaintext = cbc::decrypt(ciphertext, key, Some(&key), true)?;
+
+    if !plaintext.is_ascii() {
+        return Err(Error::InvalidPlaintext(plaintext));
+    }
+
+    Ok(utils::is_admin(plaintext))
+}
+
+/// Encrypts the comment string for the user data with AES-128-CTR,
+/// using a random key generated from the seed.
+pub fn encrypt_comment_ctr<I: AsRef<str>>(userdata: I, seed: u64) -> Result<Vec<u8>> {
+    ctr::encrypt(utils::comment_for(userdata), seeded_key(seed), 0)
+}
+
+/// Decrypts a comment string encrypted by [`encrypt_comment_ctr`],
+/// and returns whether it grants admin privileges.
+///
+/// [`encrypt_comment_ctr`]: fn.encrypt_comment_ctr.html
+pub fn is_admin_ctr<I: AsRef<[u8]>>(ciphertext: I, seed: u64) -> Result<bool> {
+    Ok(utils::is_admin(ctr::decrypt(
+        ciphertext,
+        seeded_key(seed),
+        0,
+    )?))
+}
+
+/// Returns whether the input was encryptd using ECB.
+pub fn is_ecb_encrypted<I: AsRef<[u8]>>(input: I) -> bool {
+    input
+        .as_ref()
+        .chunks(16)
+        .zip(input.as_ref().chunks(16).skip(1))
+        .any(|(a, b)| a == b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::misc;
+
+    #[test]
+    fn ecb_with_padding() {
+        for (pt, ct) in vec![
+            (&b"We all live in"[..], "3f538bb72dd0af159f18363ceb535413"),
+            (
+                &b"We all live in a"[..],
+                "ec39ebf3d7914e8f141b8afb91d3462960fa36707e45f499dba0f25b922301a5",
+            ),
+            (
+                &b"We all live in a yellow submarine"[..],
+                "ec39ebf3d7914e8f141b8afb91d34629a1a1f92f5bca30da\
+                 e57f35e708f49c8d4351d077d91e9420657dc3cd8868b376",
+            ),
+        ]
+        .into_iter()
+        {
+            assert_eq!(
+                hex::encode(ecb::encrypt(pt, b"YELLOW SUBMARINE", true).unwrap()),
+                ct
+            );
+        }
+    }
+
+    #[test]
+    fn ecb_without_padding() {
+        for (pt, ct) in vec![
+            (&b"We all live in a"[..], "ec39ebf3d7914e8f141b8afb91d34629"),
+            (
+                &b"We all live in a yellow submarin"[..],
+                "ec39ebf3d7914e8f141b8afb91d34629\
+                 a1a1f92f5bca30dae57f35e708f49c8d",
+            ),
+        ]
+        .into_iter()
+        {
+            assert_eq!(
+                hex::encode(ecb::encrypt(pt, b"YELLOW SUBMARINE", false).unwrap()),
+                ct
+            );
+        }
+    }
+
+    #[test]
+    fn ecb_roundtrip() {
+        assert_eq!(
+            ecb::decrypt(
+                ecb::encrypt(
+                    &b"We all live in a yellow submarine"[..],
+                    b"YELLOW SUBMARINE",
+                    true
+                )
+                .unwrap(),
+                b"YELLOW SUBMARINE",
+                true
+            )
+            .unwrap(),
+            &b"We all live in a yellow submarine"[..]
+        );
+    }
+
+    #[test]
+    fn cbc_roundtrip() {
+        let pt = &b"We all live in a yellow submarine"[..];
+        let iv = &b"0123456789abcdef"[..];
+        let ct = cbc::encrypt(pt, b"YELLOW SUBMARINE", Some(iv)).unwrap();
+
+        assert_eq!(
+            cbc::decrypt(&ct, b"YELLOW SUBMARINE", Some(iv), true).unwrap(),
+            pt
+        );
+        assert_eq!(
+            cbc::decrypt(&ct, b"YELLOW SUBMARINE", Some(iv), false).unwrap(),
+            misc::pkcs7(pt, 16)
+        );
+    }
+
+    #[test]
+    fn cbc_bad_padding() {
+        let mut ct = cbc::encrypt(b"YELLOW SUBMARINE", b"YELLOW SUBMARINE", None).unwrap();
+
+        // Corrupt the last byte of the padding block
+        ct[15] ^= 0x01;
+
+        assert_eq!(
+            cbc::decrypt(&ct, b"YELLOW SUBMARINE", None, true).unwrap_err(),
+            Error::Padding(misc::PaddingError::InconsistentPadBytes)
+        );
+    }
+
+    #[test]
+    fn aes_key_sizes() {
+        // FIPS-197, Appendix C
+        let pt = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+
+        for (key, ct) in vec![
+            (
+                "000102030405060708090a0b0c0d0e0f",
+                "69c4e0d86a7b0430d8cdb78070b4c55a",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f1011121314151617",
+                "dda97ca4864cdfe06eaf70a0ec0d7191",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+                "8ea2b7ca516745bfeafc49904b496089",
+            ),
+        ]
+        .into_iter()
+        {
+            let cipher = Aes::new(hex::decode(key).unwrap()).unwrap();
+
+            let mut block = pt.clone();
+            cipher.encrypt_block(&mut block);
+            assert_eq!(hex::encode(&block), ct);
+
+            cipher.decrypt_block(&mut block);
+            assert_eq!(block, pt);
+        }
+
+        assert!(Aes::new(b"YELLOW SUBMARIN").is_err());
+    }
+
+    #[test]
+    fn cbc_with_aes256() {
+        let key = b"YELLOW SUBMARINEYELLOW SUBMARINE";
+        let pt = &b"We all live in a yellow submarine"[..];
+        let ct = cbc::encrypt(pt, key, None).unwrap();
+
+        assert_eq!(ct.len(), 48);
+        assert_eq!(cbc::decrypt(ct, key, None, true).unwrap(), pt);
+    }
+
+    #[test]
+    fn ctr_cryptopals_layout() {
+        assert_eq!(
+            ctr::decrypt(
+                utils::from_base64(
+                    "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ=="
+                )
+                .unwrap(),
+                b"YELLOW SUBMARINE",
+                0
+            )
+            .unwrap(),
+            &b"Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby "[..]
+        );
+    }
+
+    #[test]
+    fn ctr_nist_layout() {
+        // NIST SP 800-38A, F.5.1 CTR-AES128.Encrypt
+        let key = hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let nonce = hex::decode("f0f1f2f3f4f5f6f7").unwrap();
+
+        let mut ctr = ctr::Ctr::new(key, nonce, ctr::Layout::NIST)
+            .unwrap()
+            .with_initial_counter(0xf8f9fafbfcfdfeff);
+
+        assert_eq!(
+            hex::encode(
+                ctr.process(
+                    hex::decode(
+                        "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+                         30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710"
+                    )
+                    .unwrap()
+                )
+                .unwrap()
+            ),
+            "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff\
+             5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee"
+        );
+    }
+
+    #[test]
+    fn ctr_seek() {
+        let pt = &b"We all live in a yellow submarine, a yellow submarine"[..];
+        let ct = ctr::encrypt(pt, b"YELLOW SUBMARINE", 42).unwrap();
+
+        let mut ctr =
+            ctr::Ctr::new(b"YELLOW SUBMARINE", 42u64.to_le_bytes(), Default::default()).unwrap();
+
+        for offset in 0..pt.len() {
+            ctr.seek(offset as u64);
+            assert_eq!(ctr.process(&ct[offset..]).unwrap(), &pt[offset..]);
+            assert_eq!(ctr.position(), pt.len() as u64);
+        }
+    }
+
+    #[test]
+    fn ctr_edit() {
+        let key = b"YELLOW SUBMARINE";
+        let ct = ctr::encrypt(b"We all live in a yellow submarine", key, 7).unwrap();
+
+        let edited = ctr::edit(&ct, key, 7, 17, b"purple").unwrap();
+        assert_eq!(&edited[..17], &ct[..17]);
+        assert_eq!(&edited[23..], &ct[23..]);
+        assert_eq!(
+            ctr::decrypt(edited, key, 7).unwrap(),
+            &b"We all live in a purple submarine"[..]
+        );
+
+        let extended = ctr::edit(&ct, key, 7, 24, b"Submarine!").unwrap();
+        assert_eq!(
+            ctr::decrypt(extended, key, 7).unwrap(),
+            &b"We all live in a yellow Submarine!"[..]
+        );
+
+        assert!(ctr::edit(&ct, key, 7, 34, b"!").is_err());
+    }
+
+    #[test]
+    fn ctr_invalid_layout() {
+        let layout = ctr::Layout {
+            nonce_len: 4,
+            counter_len: 4,
+            endianness: ctr::Endianness::Big,
+        };
+
+        assert!(ctr::Ctr::new(b"YELLOW SUBMARINE", [0; 4], layout).is_err());
+        assert!(ctr::Ctr::new(b"YELLOW SUBMARINE", [0; 4], ctr::Layout::NIST).is_err());
+    }
+}
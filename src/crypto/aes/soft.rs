@@ -0,0 +1,482 @@
+//! A from-scratch implementation of the AES block cipher, as described in FIPS-197.
+//!
+//! Unlike the OpenSSL backend, this implementation exposes the individual round
+//! transformations and supports an arbitrary number of rounds, which makes it
+//! suitable for experimenting with reduced-round variants of the cipher.
+
+use super::BLOCK_SIZE;
+use crate::{crypto::cipher::BlockCipher, Error, Result};
+
+/// The AES state, stored in column-major order as in FIPS-197.
+pub type State = [u8; BLOCK_SIZE];
+
+/// The AES S-box.
+pub const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+/// The inverse AES S-box.
+pub const INV_SBOX: [u8; 256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
+/// Multiplies a field element by `x` in GF(2^8).
+fn xtime(a: u8) -> u8 {
+    (a << 1) ^ if a & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+/// Multiplies two field elements in GF(2^8).
+fn gmul(mut a: u8, mut b: u8) -> u8 {
+    let mut r = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            r ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    r
+}
+
+/// Returns the number of rounds specified by FIPS-197 for the given key length.
+pub fn rounds_for_key(key_len: usize) -> Option<usize> {
+    match key_len {
+        16 => Some(10),
+        24 => Some(12),
+        32 => Some(14),
+        _ => None,
+    }
+}
+
+/// Expands the key into `rounds + 1` round keys.
+///
+/// The standard key schedule is simply run for as long as needed,
+/// so any number of rounds is supported.
+///
+/// # Panics
+///
+/// Panics if the key is not 16, 24 or 32 bytes long.
+pub fn expand_key(key: &[u8], rounds: usize) -> Vec<State> {
+    assert!(
+        rounds_for_key(key.len()).is_some(),
+        "Invalid AES key length"
+    );
+
+    let nk = key.len() / 4;
+    let nw = 4 * (rounds + 1);
+
+    let mut words = key
+        .chunks(4)
+        .map(|w| [w[0], w[1], w[2], w[3]])
+        .collect::<Vec<_>>();
+
+    let mut rcon = 1;
+
+    for i in nk..nw {
+        let mut t = words[i - 1];
+
+        if i % nk == 0 {
+            t = [
+                SBOX[t[1] as usize] ^ rcon,
+                SBOX[t[2] as usize],
+                SBOX[t[3] as usize],
+                SBOX[t[0] as usize],
+            ];
+            rcon = xtime(rcon);
+        } else if nk > 6 && i % nk == 4 {
+            t = [
+                SBOX[t[0] as usize],
+                SBOX[t[1] as usize],
+                SBOX[t[2] as usize],
+                SBOX[t[3] as usize],
+            ];
+        }
+
+        let prev = words[i - nk];
+        words.push([
+            prev[0] ^ t[0],
+            prev[1] ^ t[1],
+            prev[2] ^ t[2],
+            prev[3] ^ t[3],
+        ]);
+    }
+
+    words
+        .chunks(4)
+        .take(rounds + 1)
+        .map(|ws| {
+            let mut rk = [0; BLOCK_SIZE];
+            for (i, w) in ws.iter().enumerate() {
+                rk[4 * i..4 * i + 4].copy_from_slice(w);
+            }
+            rk
+        })
+        .collect()
+}
+
+/// Recovers the original AES-128 key from the round key of the given round.
+///
+/// This simply runs the key schedule backwards, which is possible because
+/// each AES-128 round key fully determines the previous one.
+pub fn invert_key_schedule(round_key: &State, round: usize) -> State {
+    let mut rk = *round_key;
+
+    for r in (1..=round).rev() {
+        let rcon = (1..r).fold(1, |rc, _| xtime(rc));
+
+        let mut prev = [0; BLOCK_SIZE];
+
+        // Words 1 to 3 only depend on the current round key
+        for i in (4..BLOCK_SIZE).rev() {
+            prev[i] = rk[i] ^ rk[i - 4];
+        }
+
+        // Word 0 depends on the last word of the previous round key
+        prev[0] = rk[0] ^ SBOX[prev[13] as usize] ^ rcon;
+        prev[1] = rk[1] ^ SBOX[prev[14] as usize];
+        prev[2] = rk[2] ^ SBOX[prev[15] as usize];
+        prev[3] = rk[3] ^ SBOX[prev[12] as usize];
+
+        rk = prev;
+    }
+
+    rk
+}
+
+/// XORs the round key into the state.
+pub fn add_round_key(state: &mut State, round_key: &State) {
+    for (s, k) in state.iter_mut().zip(round_key) {
+        *s ^= k;
+    }
+}
+
+/// Applies the S-box to every byte of the state.
+pub fn sub_bytes(state: &mut State) {
+    for s in state.iter_mut() {
+        *s = SBOX[*s as usize];
+    }
+}
+
+/// Applies the inverse S-box to every byte of the state.
+pub fn inv_sub_bytes(state: &mut State) {
+    for s in state.iter_mut() {
+        *s = INV_SBOX[*s as usize];
+    }
+}
+
+/// Cyclically shifts row `r` of the state to the left by `r` positions.
+pub fn shift_rows(state: &mut State) {
+    let old = *state;
+    for r in 0..4 {
+        for c in 0..4 {
+            state[r + 4 * c] = old[r + 4 * ((c + r) % 4)];
+        }
+    }
+}
+
+/// Cyclically shifts row `r` of the state to the right by `r` positions.
+pub fn inv_shift_rows(state: &mut State) {
+    let old = *state;
+    for r in 0..4 {
+        for c in 0..4 {
+            state[r + 4 * ((c + r) % 4)] = old[r + 4 * c];
+        }
+    }
+}
+
+/// Multiplies each column of the state by the MixColumns matrix.
+pub fn mix_columns(state: &mut State) {
+    for col in state.chunks_mut(4) {
+        let a = [col[0], col[1], col[2], col[3]];
+        let t = a[0] ^ a[1] ^ a[2] ^ a[3];
+        for i in 0..4 {
+            col[i] = a[i] ^ t ^ xtime(a[i] ^ a[(i + 1) % 4]);
+        }
+    }
+}
+
+/// Multiplies each column of the state by the inverse MixColumns matrix.
+pub fn inv_mix_columns(state: &mut State) {
+    for col in state.chunks_mut(4) {
+        let a = [col[0], col[1], col[2], col[3]];
+        for i in 0..4 {
+            col[i] = gmul(a[i], 0x0e)
+                ^ gmul(a[(i + 1) % 4], 0x0b)
+                ^ gmul(a[(i + 2) % 4], 0x0d)
+                ^ gmul(a[(i + 3) % 4], 0x09);
+        }
+    }
+}
+
+/// Performs a full AES round: SubBytes, ShiftRows, MixColumns and AddRoundKey.
+pub fn round(state: &mut State, round_key: &State) {
+    sub_bytes(state);
+    shift_rows(state);
+    mix_columns(state);
+    add_round_key(state, round_key);
+}
+
+/// Performs the final AES round, which omits the MixColumns step.
+pub fn final_round(state: &mut State, round_key: &State) {
+    sub_bytes(state);
+    shift_rows(state);
+    add_round_key(state, round_key);
+}
+
+/// Inverts a full AES round.
+pub fn inv_round(state: &mut State, round_key: &State) {
+    add_round_key(state, round_key);
+    inv_mix_columns(state);
+    inv_shift_rows(state);
+    inv_sub_bytes(state);
+}
+
+/// Inverts the final AES round.
+pub fn inv_final_round(state: &mut State, round_key: &State) {
+    add_round_key(state, round_key);
+    inv_shift_rows(state);
+    inv_sub_bytes(state);
+}
+
+/// The AES block cipher, with a configurable number of rounds.
+///
+/// As in the full cipher, the last round omits the MixColumns step.
+#[derive(Debug, Clone)]
+pub struct AesCore {
+    round_keys: Vec<State>,
+}
+
+impl AesCore {
+    /// Creates a new cipher with the number of rounds specified by FIPS-197.
+    ///
+    /// Returns an error if the key is not 16, 24 or 32 bytes long.
+    pub fn new<K: AsRef<[u8]>>(key: K) -> Result<Self> {
+        let key = key.as_ref();
+        let rounds = rounds_for_key(key.len()).ok_or(Error::InvalidKeyLength(key.len()))?;
+        AesCore::with_rounds(key, rounds)
+    }
+
+    /// Creates a new cipher performing only `rounds` rounds.
+    ///
+    /// Returns an error if the key is not 16, 24 or 32 bytes long,
+    /// or if `rounds` is zero.
+    pub fn with_rounds<K: AsRef<[u8]>>(key: K, rounds: usize) -> Result<Self> {
+        let key = key.as_ref();
+
+        if rounds_for_key(key.len()).is_none() {
+            return Err(Error::InvalidKeyLength(key.len()));
+        }
+        if rounds == 0 {
+            return Err(Error::InvalidArgument("AES requires at least one round"));
+        }
+
+        Ok(AesCore {
+            round_keys: expand_key(key, rounds),
+        })
+    }
+
+    /// Returns the number of rounds performed by the cipher.
+    pub fn rounds(&self) -> usize {
+        self.round_keys.len() - 1
+    }
+
+    /// Returns the expanded round keys, starting from the initial whitening key.
+    pub fn round_keys(&self) -> &[State] {
+        &self.round_keys
+    }
+}
+
+impl BlockCipher for AesCore {
+    fn block_size(&self) -> usize {
+        BLOCK_SIZE
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        assert_eq!(block.len(), BLOCK_SIZE, "Invalid block size");
+
+        let mut state = [0; BLOCK_SIZE];
+        state.copy_from_slice(block);
+
+        let n = self.rounds();
+
+        add_round_key(&mut state, &self.round_keys[0]);
+
+        for rk in &self.round_keys[1..n] {
+            round(&mut state, rk);
+        }
+
+        final_round(&mut state, &self.round_keys[n]);
+
+        block.copy_from_slice(&state);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        assert_eq!(block.len(), BLOCK_SIZE, "Invalid block size");
+
+        let mut state = [0; BLOCK_SIZE];
+        state.copy_from_slice(block);
+
+        let n = self.rounds();
+
+        inv_final_round(&mut state, &self.round_keys[n]);
+
+        for rk in self.round_keys[1..n].iter().rev() {
+            inv_round(&mut state, rk);
+        }
+
+        add_round_key(&mut state, &self.round_keys[0]);
+
+        block.copy_from_slice(&state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::aes::ecb;
+
+    #[test]
+    fn key_expansion() {
+        // FIPS-197, Appendix A.1
+        let rks = expand_key(
+            &hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap(),
+            10,
+        );
+
+        assert_eq!(rks.len(), 11);
+        assert_eq!(hex::encode(rks[1]), "a0fafe1788542cb123a339392a6c7605");
+        assert_eq!(hex::encode(rks[10]), "d014f9a8c9ee2589e13f0cc8b6630ca6");
+    }
+
+    #[test]
+    fn key_schedule_inversion() {
+        let key = hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let rks = expand_key(&key, 10);
+
+        for (r, rk) in rks.iter().enumerate() {
+            assert_eq!(&invert_key_schedule(rk, r)[..], &key[..]);
+        }
+    }
+
+    #[test]
+    fn fips_197_vectors() {
+        // FIPS-197, Appendix C
+        let pt = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+
+        for (key, ct) in vec![
+            (
+                "000102030405060708090a0b0c0d0e0f",
+                "69c4e0d86a7b0430d8cdb78070b4c55a",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f1011121314151617",
+                "dda97ca4864cdfe06eaf70a0ec0d7191",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+                "8ea2b7ca516745bfeafc49904b496089",
+            ),
+        ]
+        .into_iter()
+        {
+            let cipher = AesCore::new(hex::decode(key).unwrap()).unwrap();
+
+            let mut block = pt.clone();
+            cipher.encrypt_block(&mut block);
+            assert_eq!(hex::encode(&block), ct);
+
+            cipher.decrypt_block(&mut block);
+            assert_eq!(block, pt);
+        }
+    }
+
+    #[test]
+    fn ecb_with_padding() {
+        let cipher = AesCore::new(b"YELLOW SUBMARINE").unwrap();
+
+        for (pt, ct) in vec![
+            (&b"We all live in"[..], "3f538bb72dd0af159f18363ceb535413"),
+            (
+                &b"We all live in a"[..],
+                "ec39ebf3d7914e8f141b8afb91d3462960fa36707e45f499dba0f25b922301a5",
+            ),
+            (
+                &b"We all live in a yellow submarine"[..],
+                "ec39ebf3d7914e8f141b8afb91d34629a1a1f92f5bca30da\
+                 e57f35e708f49c8d4351d077d91e9420657dc3cd8868b376",
+            ),
+        ]
+        .into_iter()
+        {
+            assert_eq!(
+                hex::encode(ecb::encrypt_with(&cipher, pt, true).unwrap()),
+                ct
+            );
+            assert_eq!(
+                ecb::decrypt_with(&cipher, hex::decode(ct).unwrap(), true).unwrap(),
+                pt
+            );
+        }
+    }
+
+    #[test]
+    fn reduced_rounds_roundtrip() {
+        for rounds in 1..=14 {
+            let cipher = AesCore::with_rounds(b"YELLOW SUBMARINE", rounds).unwrap();
+            assert_eq!(cipher.rounds(), rounds);
+
+            let mut block = *b"We all live in a";
+            cipher.encrypt_block(&mut block);
+            assert_ne!(&block, b"We all live in a");
+
+            cipher.decrypt_block(&mut block);
+            assert_eq!(&block, b"We all live in a");
+        }
+
+        assert!(AesCore::with_rounds(b"YELLOW SUBMARINE", 0).is_err());
+    }
+
+    #[test]
+    fn round_transformations_invert() {
+        let mut state = *b"We all live in a";
+
+        shift_rows(&mut state);
+        mix_columns(&mut state);
+        sub_bytes(&mut state);
+        inv_sub_bytes(&mut state);
+        inv_mix_columns(&mut state);
+        inv_shift_rows(&mut state);
+
+        assert_eq!(&state, b"We all live in a");
+    }
+}
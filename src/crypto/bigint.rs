@@ -0,0 +1,230 @@
+//! Arbitrary-precision integer arithmetic, built on top of `num-bigint`.
+//!
+//! This module provides the number-theoretic primitives needed by the public-key
+//! challenges, along with conversions to and from the encodings used in the crate.
+
+use crate::{utils, Error, Result};
+
+use num_bigint::{RandBigInt, Sign};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+pub use num_bigint::{BigInt, BigUint};
+
+/// Computes `base^exp mod modulus`.
+///
+/// # Panics
+///
+/// Panics if `modulus` is zero.
+pub fn modexp(base: &BigUint, exp: &BigUint, modulus: &BigUint) -> BigUint {
+    base.modpow(exp, modulus)
+}
+
+/// Runs the extended Euclidean algorithm on `a` and `b`.
+///
+/// Returns `(g, x, y)` such that `g = gcd(a, b) = a*x + b*y`.
+pub fn egcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
+    let (mut old_r, mut r) = (a.clone(), b.clone());
+    let (mut old_s, mut s) = (BigInt::one(), BigInt::zero());
+    let (mut old_t, mut t) = (BigInt::zero(), BigInt::one());
+
+    while !r.is_zero() {
+        let q = &old_r / &r;
+
+        let next_r = &old_r - &q * &r;
+        old_r = std::mem::replace(&mut r, next_r);
+        let next_s = &old_s - &q * &s;
+        old_s = std::mem::replace(&mut s, next_s);
+        let next_t = &old_t - &q * &t;
+        old_t = std::mem::replace(&mut t, next_t);
+    }
+
+    (old_r, old_s, old_t)
+}
+
+/// Computes the inverse of `a` modulo `modulus`.
+///
+/// Returns `None` if `a` and `modulus` are not coprime.
+pub fn modinv(a: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    let m = BigInt::from(modulus.clone());
+    let (g, x, _) = egcd(&BigInt::from(a.clone()), &m);
+
+    if !g.is_one() {
+        return None;
+    }
+
+    x.mod_floor(&m).to_biguint()
+}
+
+/// Computes the integer `n`-th root of `x`, rounded down.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+pub fn nth_root(x: &BigUint, n: u32) -> BigUint {
+    x.nth_root(n)
+}
+
+/// Computes the integer cube root of `x`, rounded down.
+pub fn cube_root(x: &BigUint) -> BigUint {
+    x.cbrt()
+}
+
+/// Combines the congruences `x = r_i mod m_i` using the Chinese Remainder Theorem.
+///
+/// Each congruence is given as a `(r_i, m_i)` pair. Returns the smallest
+/// non-negative solution, or `None` if the moduli are not pairwise coprime.
+pub fn crt(congruences: &[(BigUint, BigUint)]) -> Option<BigUint> {
+    let product = congruences
+        .iter()
+        .fold(BigUint::one(), |acc, (_, m)| acc * m);
+
+    let mut x = BigUint::zero();
+    for (r, m) in congruences {
+        let ms = &product / m;
+        x += r * &ms * modinv(&ms, m)?;
+    }
+
+    Some(x % product)
+}
+
+/// Generates a uniformly random integer in the range `[0, bound)`.
+///
+/// # Panics
+///
+/// Panics if `bound` is zero.
+pub fn random_below(bound: &BigUint) -> BigUint {
+    rand::thread_rng().gen_biguint_below(bound)
+}
+
+/// Generates a uniformly random integer in the range `[low, high)`.
+///
+/// # Panics
+///
+/// Panics if `low >= high`.
+pub fn random_range(low: &BigUint, high: &BigUint) -> BigUint {
+    rand::thread_rng().gen_biguint_range(low, high)
+}
+
+/// Interprets big-endian bytes as an unsigned integer.
+pub fn from_bytes<I: AsRef<[u8]>>(bytes: I) -> BigUint {
+    BigUint::from_bytes_be(bytes.as_ref())
+}
+
+/// Encodes an unsigned integer as big-endian bytes, without leading zeros.
+pub fn to_bytes(n: &BigUint) -> Vec<u8> {
+    if n.is_zero() {
+        Vec::new()
+    } else {
+        n.to_bytes_be()
+    }
+}
+
+/// Parses a hex-encoded unsigned integer. Whitespace is ignored.
+pub fn from_hex<I: AsRef<str>>(s: I) -> Result<BigUint> {
+    let digits = s
+        .as_ref()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+
+    BigUint::parse_bytes(digits.as_bytes(), 16).ok_or(Error::Parse("Invalid hex integer"))
+}
+
+/// Encodes an unsigned integer as a lowercase hex string.
+pub fn to_hex(n: &BigUint) -> String {
+    n.to_str_radix(16)
+}
+
+/// Decodes a Base64-encoded big-endian unsigned integer.
+pub fn from_base64<I: AsRef<[u8]>>(input: I) -> Result<BigUint> {
+    Ok(from_bytes(utils::from_base64(input)?))
+}
+
+/// Encodes an unsigned integer as Base64-encoded big-endian bytes.
+pub fn to_base64(n: &BigUint) -> String {
+    base64::encode(to_bytes(n))
+}
+
+/// Converts a signed integer to an unsigned one, failing if it is negative.
+pub fn to_unsigned(n: &BigInt) -> Result<BigUint> {
+    match n.sign() {
+        Sign::Minus => Err(Error::InvalidArgument("Negative integer")),
+        _ => Ok(n.magnitude().clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(n: u64) -> BigUint {
+        BigUint::from(n)
+    }
+
+    #[test]
+    fn modexp_works() {
+        assert_eq!(modexp(&big(4), &big(13), &big(497)), big(445));
+        assert_eq!(modexp(&big(5), &big(0), &big(7)), big(1));
+    }
+
+    #[test]
+    fn egcd_works() {
+        let (g, x, y) = egcd(&BigInt::from(240), &BigInt::from(46));
+
+        assert_eq!(g, BigInt::from(2));
+        assert_eq!(x * 240 + y * 46, BigInt::from(2));
+    }
+
+    #[test]
+    fn modinv_works() {
+        assert_eq!(modinv(&big(17), &big(3120)), Some(big(2753)));
+        assert_eq!(modinv(&big(3), &big(11)), Some(big(4)));
+        assert_eq!(modinv(&big(6), &big(9)), None);
+    }
+
+    #[test]
+    fn roots_work() {
+        let x = from_hex("1234567890abcdef1234567890abcdef").unwrap();
+
+        assert_eq!(cube_root(&x.pow(3)), x);
+        assert_eq!(cube_root(&(x.pow(3) - 1u32)), &x - 1u32);
+        assert_eq!(nth_root(&x.pow(5), 5), x);
+    }
+
+    #[test]
+    fn crt_works() {
+        assert_eq!(
+            crt(&[(big(2), big(3)), (big(3), big(5)), (big(2), big(7))]),
+            Some(big(23))
+        );
+        assert_eq!(crt(&[(big(1), big(4)), (big(3), big(6))]), None);
+    }
+
+    #[test]
+    fn random_below_works() {
+        let bound = big(1000);
+
+        for _ in 0..100 {
+            assert!(random_below(&bound) < bound);
+
+            let x = random_range(&big(10), &big(20));
+            assert!(x >= big(10) && x < big(20));
+        }
+    }
+
+    #[test]
+    fn conversions_roundtrip() {
+        let n = from_hex("00ff 00ff\n0102").unwrap();
+
+        assert_eq!(to_hex(&n), "ff00ff0102");
+        assert_eq!(to_bytes(&n), vec![0xff, 0x00, 0xff, 0x01, 0x02]);
+        assert_eq!(from_bytes(to_bytes(&n)), n);
+        assert_eq!(from_base64(to_base64(&n)).unwrap(), n);
+        assert_eq!(to_bytes(&big(0)), Vec::<u8>::new());
+
+        assert!(from_hex("xyz").is_err());
+        assert_eq!(to_unsigned(&BigInt::from(5)).unwrap(), big(5));
+        assert!(to_unsigned(&BigInt::from(-5)).is_err());
+    }
+}
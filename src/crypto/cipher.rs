@@ -0,0 +1,25 @@
+/// A block cipher, operating on fixed-size blocks of data.
+///
+/// Modes of operation such as those in [`aes::ecb`] and [`aes::cbc`] are generic
+/// over this trait, so that they can be used with any block cipher.
+///
+/// [`aes::ecb`]: ../aes/ecb/index.html
+/// [`aes::cbc`]: ../aes/cbc/index.html
+pub trait BlockCipher {
+    /// Returns the size of a block, in bytes.
+    fn block_size(&self) -> usize;
+
+    /// Encrypts a single block in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block` is not exactly `block_size()` bytes long.
+    fn encrypt_block(&self, block: &mut [u8]);
+
+    /// Decrypts a single block in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block` is not exactly `block_size()` bytes long.
+    fn decrypt_block(&self, block: &mut [u8]);
+}
@@ -0,0 +1,156 @@
+//! Finite-field Diffie-Hellman key exchange.
+
+use crate::{
+    crypto::{
+        aes::{self, cbc},
+        bigint::{self, BigUint},
+        hash::sha1,
+    },
+    Error, Result,
+};
+
+use num_traits::One;
+
+/// Hex encoding of the 1536-bit MODP prime from RFC 3526, as given in Set 5 Challenge 33.
+const NIST_PRIME: &str = "\
+    ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74\
+    020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437\
+    4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed\
+    ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05\
+    98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb\
+    9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff";
+
+/// The parameters of a Diffie-Hellman group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DhGroup {
+    /// The prime modulus.
+    pub p: BigUint,
+    /// The generator.
+    pub g: BigUint,
+    /// The order of the subgroup generated by `g`, if known.
+    pub q: Option<BigUint>,
+}
+
+/// A Diffie-Hellman key pair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keypair {
+    /// The private exponent.
+    pub private: BigUint,
+    /// The public value, `g^private mod p`.
+    pub public: BigUint,
+}
+
+impl DhGroup {
+    /// Creates a group from its parameters.
+    pub fn new(p: BigUint, g: BigUint, q: Option<BigUint>) -> Self {
+        DhGroup { p, g, q }
+    }
+
+    /// The 1536-bit MODP group with generator 2.
+    ///
+    /// The modulus is a safe prime, and 2 generates the subgroup of order `(p - 1) / 2`.
+    pub fn nist() -> Self {
+        let p = bigint::from_hex(NIST_PRIME).expect("invalid NIST prime");
+        let q = (&p - 1u32) >> 1;
+
+        DhGroup::new(p, BigUint::from(2u32), Some(q))
+    }
+
+    /// Generates a random key pair in this group.
+    ///
+    /// The private exponent is drawn from `[1, q)` if the subgroup order is known,
+    /// or from `[1, p - 1)` otherwise.
+    pub fn generate_keypair(&self) -> Keypair {
+        let bound = self.q.clone().unwrap_or_else(|| &self.p - 1u32);
+        let private = bigint::random_range(&BigUint::one(), &bound);
+
+        self.keypair_from_private(private)
+    }
+
+    /// Builds the key pair corresponding to a private exponent.
+    pub fn keypair_from_private(&self, private: BigUint) -> Keypair {
+        let public = bigint::modexp(&self.g, &private, &self.p);
+        Keypair { private, public }
+    }
+
+    /// Computes the shared secret between our key pair and the peer's public value.
+    ///
+    /// The peer's public value is deliberately not validated.
+    pub fn shared_secret(&self, keypair: &Keypair, peer_public: &BigUint) -> BigUint {
+        bigint::modexp(peer_public, &keypair.private, &self.p)
+    }
+}
+
+/// Derives an AES-128 key from a shared secret, truncating its SHA-1 digest.
+pub fn derive_key(secret: &BigUint) -> [u8; 16] {
+    let mut key = [0; 16];
+    key.copy_from_slice(&sha1::digest(bigint::to_bytes(secret))[..16]);
+    key
+}
+
+/// Encrypts a message with AES-128-CBC under the key derived from a shared secret.
+///
+/// A random IV is used and appended to the ciphertext.
+pub fn encrypt<I: AsRef<[u8]>>(secret: &BigUint, message: I) -> Result<Vec<u8>> {
+    let iv = aes::random_key();
+    let mut ciphertext = cbc::encrypt(message, derive_key(secret), Some(&iv))?;
+
+    ciphertext.extend_from_slice(&iv);
+    Ok(ciphertext)
+}
+
+/// Decrypts a message produced by [`encrypt`](fn.encrypt.html).
+pub fn decrypt<I: AsRef<[u8]>>(secret: &BigUint, data: I) -> Result<Vec<u8>> {
+    let data = data.as_ref();
+
+    if data.len() < 2 * aes::BLOCK_SIZE {
+        return Err(Error::InvalidInputLength(data.len()));
+    }
+
+    let (ciphertext, iv) = data.split_at(data.len() - aes::BLOCK_SIZE);
+    cbc::decrypt(ciphertext, derive_key(secret), Some(iv), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_group_exchange() {
+        let group = DhGroup::new(BigUint::from(37u32), BigUint::from(5u32), None);
+
+        for _ in 0..10 {
+            let (a, b) = (group.generate_keypair(), group.generate_keypair());
+
+            assert!(a.private >= BigUint::one() && a.private < BigUint::from(36u32));
+            assert_eq!(
+                group.shared_secret(&a, &b.public),
+                group.shared_secret(&b, &a.public)
+            );
+        }
+    }
+
+    #[test]
+    fn nist_group() {
+        let group = DhGroup::nist();
+
+        assert_eq!(group.p.bits(), 1536);
+        assert_eq!(
+            bigint::modexp(&group.g, group.q.as_ref().unwrap(), &group.p),
+            BigUint::one()
+        );
+    }
+
+    #[test]
+    fn encrypted_messages() {
+        let group = DhGroup::nist();
+        let (a, b) = (group.generate_keypair(), group.generate_keypair());
+
+        let ciphertext = encrypt(&group.shared_secret(&a, &b.public), "Hello, Bob!").unwrap();
+        let plaintext = decrypt(&group.shared_secret(&b, &a.public), &ciphertext).unwrap();
+
+        assert_eq!(plaintext, b"Hello, Bob!");
+        assert_ne!(decrypt(&BigUint::one(), &ciphertext).ok(), Some(plaintext));
+        assert!(decrypt(&BigUint::one(), &ciphertext[..16]).is_err());
+    }
+}
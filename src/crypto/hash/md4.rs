@@ -0,0 +1,118 @@
+//! MD4, as specified in RFC 1320.
+
+use super::{md_padding_le, words_le, Hash};
+
+/// Size of an MD4 digest, in bytes.
+pub const OUTPUT_SIZE: usize = 16;
+
+/// Initial hash value.
+const H0: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+
+/// Order in which message words are processed in the second round.
+const ROUND_2_ORDER: [usize; 16] = [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15];
+
+/// Order in which message words are processed in the third round.
+const ROUND_3_ORDER: [usize; 16] = [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15];
+
+md_hasher! {
+    /// A resumable MD4 hasher.
+    Md4 {
+        state: [u32; 4],
+        init: H0,
+        output: OUTPUT_SIZE,
+        encode: u32::to_le_bytes,
+        decode: words_le,
+        padding: md_padding_le,
+        compress: compress,
+    }
+}
+
+/// Processes a single 64-byte block.
+fn compress(state: &mut [u32; 4], block: &[u8]) {
+    let mut x = [0u32; 16];
+    words_le(block, &mut x);
+
+    let mut h = *state;
+
+    // Each step updates a, d, c and b in turn, rotating the roles of the registers
+    let mut step = |i: usize, f: fn(u32, u32, u32) -> u32, k: usize, s: u32, c: u32| {
+        let j = (4 - i % 4) % 4;
+        h[j] = h[j]
+            .wrapping_add(f(h[(j + 1) % 4], h[(j + 2) % 4], h[(j + 3) % 4]))
+            .wrapping_add(x[k])
+            .wrapping_add(c)
+            .rotate_left(s);
+    };
+
+    for i in 0..16 {
+        step(i, |x, y, z| (x & y) | (!x & z), i, [3, 7, 11, 19][i % 4], 0);
+    }
+    for i in 0..16 {
+        step(
+            i,
+            |x, y, z| (x & y) | (x & z) | (y & z),
+            ROUND_2_ORDER[i],
+            [3, 5, 9, 13][i % 4],
+            0x5a82_7999,
+        );
+    }
+    for i in 0..16 {
+        step(
+            i,
+            |x, y, z| x ^ y ^ z,
+            ROUND_3_ORDER[i],
+            [3, 9, 11, 15][i % 4],
+            0x6ed9_eba1,
+        );
+    }
+
+    for (s, v) in state.iter_mut().zip(&h) {
+        *s = s.wrapping_add(*v);
+    }
+}
+
+/// Computes the MD4 digest of the input.
+pub fn digest<I: AsRef<[u8]>>(input: I) -> [u8; OUTPUT_SIZE] {
+    Md4::digest(input)
+}
+
+/// Computes the secret-prefix MAC `MD4(key || message)`.
+pub fn secret_prefix_mac<K, M>(key: K, message: M) -> [u8; OUTPUT_SIZE]
+where
+    K: AsRef<[u8]>,
+    M: AsRef<[u8]>,
+{
+    let mut hasher = Md4::new();
+    hasher.update(key);
+    hasher.update(message);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc_1320_vectors() {
+        for (input, output) in &[
+            ("", "31d6cfe0d16ae931b73c59d7e0c089c0"),
+            ("a", "bde52cb31de33e46245e05fbdbd6fb24"),
+            ("abc", "a448017aaf21d8525fc10ae87aa6729d"),
+            ("message digest", "d9130a8164549fe818874806e1c7014b"),
+            (
+                "abcdefghijklmnopqrstuvwxyz",
+                "d79e1c308aa5bbcdeea8ed63df412da9",
+            ),
+            (
+                "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "043f8582f241db351ce627e153e7f0e4",
+            ),
+            (
+                "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "e33b4ddc9c38f2199c3e7b164fcc0536",
+            ),
+        ] {
+            assert_eq!(hex::encode(digest(input)), *output);
+        }
+    }
+}
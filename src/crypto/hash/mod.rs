@@ -0,0 +1,248 @@
+//! Hash functions implemented from scratch, exposing their internal state.
+//!
+//! All of them follow the Merkle–Damgård construction over 64-byte blocks,
+//! so that their state can be resumed to mount length-extension attacks.
+
+use crate::{Error, Result};
+
+/// Defines a resumable Merkle–Damgård hasher with a state of 32-bit words.
+///
+/// The digest is the state encoded with `encode`; `decode` turns it back into words.
+/// Blocks are processed by `compress`, and messages are padded with `padding`.
+macro_rules! md_hasher {
+    (
+        $(#[$attr:meta])*
+        $name:ident {
+            state: [u32; $words:expr],
+            init: $h0:expr,
+            output: $output:expr,
+            encode: $encode:path,
+            decode: $decode:path,
+            padding: $padding:path,
+            compress: $compress:path,
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        pub struct $name {
+            state: [u32; $words],
+            len: u64,
+            buffer: Vec<u8>,
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name::new()
+            }
+        }
+
+        impl $name {
+            /// Creates a new hasher in the standard initial state.
+            pub fn new() -> Self {
+                $name {
+                    state: $h0,
+                    len: 0,
+                    buffer: Vec::with_capacity($crate::crypto::hash::MD_BLOCK_SIZE),
+                }
+            }
+        }
+
+        impl $crate::crypto::hash::Hash for $name {
+            const BLOCK_SIZE: usize = $crate::crypto::hash::MD_BLOCK_SIZE;
+            const OUTPUT_SIZE: usize = $output;
+
+            type Output = [u8; $output];
+            type State = [u32; $words];
+
+            fn update<I: AsRef<[u8]>>(&mut self, data: I) {
+                let state = &mut self.state;
+                $crate::crypto::hash::md_update(
+                    &mut self.buffer,
+                    &mut self.len,
+                    data.as_ref(),
+                    |block| $compress(state, block),
+                );
+            }
+
+            fn finalize(mut self) -> Self::Output {
+                let len = self.len + self.buffer.len() as u64;
+                self.update($padding(len));
+
+                let mut digest = [0; $output];
+                for (out, word) in digest.chunks_mut(4).zip(&self.state) {
+                    out.copy_from_slice(&$encode(*word));
+                }
+                digest
+            }
+
+            fn state(&self) -> Self::State {
+                self.state
+            }
+
+            fn from_state(state: Self::State, len: u64) -> $crate::Result<Self> {
+                $crate::crypto::hash::check_resume_len(len)?;
+
+                Ok($name {
+                    state,
+                    len,
+                    buffer: Vec::with_capacity($crate::crypto::hash::MD_BLOCK_SIZE),
+                })
+            }
+
+            fn from_digest(digest: &Self::Output, len: u64) -> $crate::Result<Self> {
+                let mut state = [0; $words];
+                $decode(digest, &mut state);
+
+                $name::from_state(state, len)
+            }
+
+            fn padding(len: u64) -> Vec<u8> {
+                $padding(len)
+            }
+        }
+    };
+}
+
+pub mod md4;
+pub mod md5;
+pub mod sha1;
+pub mod sha256;
+
+/// A cryptographic hash function.
+///
+/// Hashers can be resumed from an arbitrary internal state, which makes it
+/// possible to extend a message knowing only its digest and its length.
+pub trait Hash: Default {
+    /// Size of the blocks processed by the compression function, in bytes.
+    const BLOCK_SIZE: usize;
+    /// Size of a digest, in bytes.
+    const OUTPUT_SIZE: usize;
+
+    /// The digest type.
+    type Output: AsRef<[u8]>;
+    /// The internal chaining state.
+    type State;
+
+    /// Feeds more data into the hasher.
+    fn update<I: AsRef<[u8]>>(&mut self, data: I);
+
+    /// Pads the message and returns its digest.
+    fn finalize(self) -> Self::Output;
+
+    /// Returns the internal state.
+    fn state(&self) -> Self::State;
+
+    /// Resumes hashing from an arbitrary state, after `len` bytes have been processed.
+    ///
+    /// Returns an error if `len` is not a multiple of the block size.
+    fn from_state(state: Self::State, len: u64) -> Result<Self>;
+
+    /// Resumes hashing from the state encoded in `digest`, after `len` bytes have been processed.
+    fn from_digest(digest: &Self::Output, len: u64) -> Result<Self>;
+
+    /// Returns the padding appended to a message of `len` bytes before hashing it.
+    fn padding(len: u64) -> Vec<u8>;
+
+    /// Computes the digest of the input in one go.
+    fn digest<I: AsRef<[u8]>>(input: I) -> Self::Output {
+        let mut hasher = Self::default();
+        hasher.update(input);
+        hasher.finalize()
+    }
+}
+
+/// Size of a Merkle–Damgård block, in bytes.
+pub const MD_BLOCK_SIZE: usize = 64;
+
+/// Returns the padding appended to a message of `len` bytes,
+/// with the message length in bits encoded as a big-endian 64-bit integer.
+pub fn md_padding_be(len: u64) -> Vec<u8> {
+    md_padding(len, &(len.wrapping_mul(8)).to_be_bytes())
+}
+
+/// Returns the padding appended to a message of `len` bytes,
+/// with the message length in bits encoded as a little-endian 64-bit integer.
+pub fn md_padding_le(len: u64) -> Vec<u8> {
+    md_padding(len, &(len.wrapping_mul(8)).to_le_bytes())
+}
+
+/// A single 1 bit, then zeros up to 8 bytes short of a block boundary, then the length.
+fn md_padding(len: u64, encoded_len: &[u8; 8]) -> Vec<u8> {
+    let bs = MD_BLOCK_SIZE as u64;
+    let zeros = (bs + bs - 9 - len % bs) % bs;
+
+    let mut padding = vec![0x80];
+    padding.resize(1 + zeros as usize, 0);
+    padding.extend_from_slice(encoded_len);
+    padding
+}
+
+/// Feeds `data` to the compression function one block at a time,
+/// keeping any incomplete block in `buffer` and counting processed bytes in `len`.
+fn md_update<F: FnMut(&[u8])>(
+    buffer: &mut Vec<u8>,
+    len: &mut u64,
+    mut data: &[u8],
+    mut compress: F,
+) {
+    while !data.is_empty() {
+        let n = usize::min(MD_BLOCK_SIZE - buffer.len(), data.len());
+
+        buffer.extend_from_slice(&data[..n]);
+        data = &data[n..];
+
+        if buffer.len() == MD_BLOCK_SIZE {
+            compress(buffer);
+            buffer.clear();
+            *len += MD_BLOCK_SIZE as u64;
+        }
+    }
+}
+
+/// Checks that hashing can be resumed after `len` processed bytes.
+fn check_resume_len(len: u64) -> Result<()> {
+    // `u64::is_multiple_of` needs Rust 1.87, which is too recent for this crate
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    if len % MD_BLOCK_SIZE as u64 == 0 {
+        Ok(())
+    } else {
+        Err(Error::InvalidArgument(
+            "Processed length must be a multiple of the block size",
+        ))
+    }
+}
+
+/// Decodes a digest into its big-endian 32-bit words.
+fn words_be(digest: &[u8], state: &mut [u32]) {
+    for (s, word) in state.iter_mut().zip(digest.chunks(4)) {
+        *s = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+    }
+}
+
+/// Decodes a digest into its little-endian 32-bit words.
+fn words_le(digest: &[u8], state: &mut [u32]) {
+    for (s, word) in state.iter_mut().zip(digest.chunks(4)) {
+        *s = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn md_padding_aligns_to_blocks() {
+        for len in 0..200 {
+            let padding = md_padding_be(len);
+
+            assert_eq!((len as usize + padding.len()) % MD_BLOCK_SIZE, 0);
+            assert!(padding.len() > 8 && padding.len() <= MD_BLOCK_SIZE + 8);
+            assert_eq!(padding[0], 0x80);
+            assert_eq!(&padding[padding.len() - 8..], &(len * 8).to_be_bytes());
+            assert_eq!(
+                &md_padding_le(len)[padding.len() - 8..],
+                &(len * 8).to_le_bytes()
+            );
+        }
+    }
+}
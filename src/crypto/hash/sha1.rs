@@ -0,0 +1,120 @@
+//! SHA-1, as specified in FIPS 180-4.
+
+use super::{md_padding_be, words_be, Hash};
+
+/// Size of a SHA-1 digest, in bytes.
+pub const OUTPUT_SIZE: usize = 20;
+
+/// Initial hash value.
+const H0: [u32; 5] = [
+    0x6745_2301,
+    0xefcd_ab89,
+    0x98ba_dcfe,
+    0x1032_5476,
+    0xc3d2_e1f0,
+];
+
+md_hasher! {
+    /// A resumable SHA-1 hasher.
+    Sha1 {
+        state: [u32; 5],
+        init: H0,
+        output: OUTPUT_SIZE,
+        encode: u32::to_be_bytes,
+        decode: words_be,
+        padding: md_padding_be,
+        compress: compress,
+    }
+}
+
+/// Processes a single 64-byte block.
+fn compress(state: &mut [u32; 5], block: &[u8]) {
+    let mut w = [0u32; 80];
+
+    words_be(block, &mut w);
+    for t in 16..80 {
+        w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+
+    for (t, &w) in w.iter().enumerate() {
+        let (f, k) = match t {
+            0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+            20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+            40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+            _ => (b ^ c ^ d, 0xca62_c1d6),
+        };
+
+        let temp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(w);
+
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+
+    for (s, v) in state.iter_mut().zip(&[a, b, c, d, e]) {
+        *s = s.wrapping_add(*v);
+    }
+}
+
+/// Computes the SHA-1 digest of the input.
+pub fn digest<I: AsRef<[u8]>>(input: I) -> [u8; OUTPUT_SIZE] {
+    Sha1::digest(input)
+}
+
+/// Computes the secret-prefix MAC `SHA1(key || message)`.
+pub fn secret_prefix_mac<K, M>(key: K, message: M) -> [u8; OUTPUT_SIZE]
+where
+    K: AsRef<[u8]>,
+    M: AsRef<[u8]>,
+{
+    let mut hasher = Sha1::new();
+    hasher.update(key);
+    hasher.update(message);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fips_180_vectors() {
+        for (input, output) in &[
+            (&b""[..], "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+            (&b"abc"[..], "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (
+                &b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"[..],
+                "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+            ),
+        ] {
+            assert_eq!(hex::encode(digest(input)), *output);
+        }
+
+        assert_eq!(
+            hex::encode(digest(vec![b'a'; 1_000_000])),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+        );
+    }
+
+    #[test]
+    fn incremental_update() {
+        let data = (0..=255).cycle().take(1000).collect::<Vec<u8>>();
+
+        for split in &[0, 1, 63, 64, 65, 500, 1000] {
+            let mut hasher = Sha1::new();
+            hasher.update(&data[..*split]);
+            hasher.update(&data[*split..]);
+
+            assert_eq!(hasher.finalize(), digest(&data));
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! HMAC, as specified in RFC 2104, generic over the crate's hash functions.
+
+use crate::crypto::hash::Hash;
+
+use std::{thread, time::Duration};
+
+/// Computes the HMAC of `message` under `key`, using the hash function `H`.
+pub fn hmac<H, K, M>(key: K, message: M) -> H::Output
+where
+    H: Hash,
+    K: AsRef<[u8]>,
+    M: AsRef<[u8]>,
+{
+    // Keys longer than a block are hashed, shorter keys are padded with zeros
+    let mut key = key.as_ref().to_vec();
+    if key.len() > H::BLOCK_SIZE {
+        key = H::digest(&key).as_ref().to_vec();
+    }
+    key.resize(H::BLOCK_SIZE, 0);
+
+    let mut inner = H::default();
+    inner.update(key.iter().map(|k| k ^ 0x36).collect::<Vec<_>>());
+    inner.update(message);
+
+    let mut outer = H::default();
+    outer.update(key.iter().map(|k| k ^ 0x5c).collect::<Vec<_>>());
+    outer.update(inner.finalize());
+    outer.finalize()
+}
+
+/// Compares two byte strings, sleeping for `delay` after each matching byte.
+///
+/// The comparison exits early on the first mismatch, so the time it takes
+/// leaks the length of the common prefix of `a` and `b`.
+pub fn insecure_compare<I: AsRef<[u8]>>(a: I, b: I, delay: Duration) -> bool {
+    let (a, b) = (a.as_ref(), b.as_ref());
+
+    for (x, y) in a.iter().zip(b) {
+        if x != y {
+            return false;
+        }
+        thread::sleep(delay);
+    }
+
+    a.len() == b.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::hash::{md4::Md4, md5::Md5, sha1::Sha1, sha256::Sha256};
+
+    #[test]
+    fn rfc_2202_vectors() {
+        assert_eq!(
+            hex::encode(hmac::<Sha1, _, _>([0x0b; 20], "Hi There")),
+            "b617318655057264e28bc0b6fb378c8ef146be00"
+        );
+        assert_eq!(
+            hex::encode(hmac::<Sha1, _, _>("Jefe", "what do ya want for nothing?")),
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"
+        );
+        assert_eq!(
+            hex::encode(hmac::<Sha1, _, _>(
+                [0xaa; 80],
+                "Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "aa4ae5e15272d00e95705637ce8a3b55ed402112"
+        );
+    }
+
+    #[test]
+    fn rfc_4231_vectors() {
+        assert_eq!(
+            hex::encode(hmac::<Sha256, _, _>("Jefe", "what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn rfc_2104_vectors() {
+        assert_eq!(
+            hex::encode(hmac::<Md5, _, _>("Jefe", "what do ya want for nothing?")),
+            "750c783e6ab0b503eaa86e310a5db738"
+        );
+    }
+
+    #[test]
+    fn hmac_is_generic() {
+        let a = hmac::<Md4, _, _>("key", "message");
+        let b = hmac::<Md4, _, _>("key", "massage");
+
+        assert_eq!(a.len(), 16);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn insecure_compare_works() {
+        let delay = Duration::from_millis(0);
+
+        assert!(insecure_compare(&b"foobar"[..], b"foobar", delay));
+        assert!(!insecure_compare(&b"foobar"[..], b"foobaz", delay));
+        assert!(!insecure_compare(&b"foobar"[..], b"foo", delay));
+    }
+}
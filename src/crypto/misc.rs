@@ -1,3 +1,10 @@
+use crate::{oracle::EncryptionOracle, Error};
+
+use std::{error, fmt};
+
+/// Largest block size tried by [`discover_block_size`](fn.discover_block_size.html).
+const MAX_BLOCK_SIZE: usize = 256;
+
 /// Computes the element-wise XOR of two byte slices.
 ///
 /// If `b` is shorter than `a`, it is replicated until reaching the same size.
@@ -10,10 +17,7 @@ pub fn xor<I: AsRef<[u8]>>(a: I, b: I) -> Vec<u8> {
 
     assert!(a.len() >= b.len());
 
-    a.into_iter()
-        .zip(b.into_iter().cycle())
-        .map(|(a, b)| a ^ b)
-        .collect()
+    a.iter().zip(b.iter().cycle()).map(|(a, b)| a ^ b).collect()
 }
 
 /// Pads the input to a multiple of `size` bytes by using the PKCS#7 padding scheme.
@@ -25,26 +29,81 @@ pub fn pkcs7<I: AsRef<[u8]>>(input: I, size: u8) -> Vec<u8> {
     input
 }
 
+/// Error returned when validating PKCS#7 padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddingError {
+    /// The input is empty or not a multiple of the block size.
+    BadLength,
+    /// The last byte of the input is zero.
+    ZeroPadByte,
+    /// The padding bytes do not all match the last byte,
+    /// or the last byte is larger than the block size.
+    InconsistentPadBytes,
+}
+
+impl fmt::Display for PaddingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PaddingError::BadLength => write!(f, "bad length for padded input"),
+            PaddingError::ZeroPadByte => write!(f, "zero pad byte"),
+            PaddingError::InconsistentPadBytes => write!(f, "inconsistent pad bytes"),
+        }
+    }
+}
+
+impl error::Error for PaddingError {}
+
+/// Validates and strips the PKCS#7 padding from the input.
+///
+/// The input must be a multiple of `size` bytes long.
+pub fn pkcs7_unpad<I: AsRef<[u8]>>(input: I, size: u8) -> Result<Vec<u8>, PaddingError> {
+    let input = input.as_ref();
+    let size = usize::from(size);
+
+    if input.is_empty() || size == 0 || input.len() % size != 0 {
+        return Err(PaddingError::BadLength);
+    }
+
+    let n = usize::from(input[input.len() - 1]);
+
+    if n == 0 {
+        return Err(PaddingError::ZeroPadByte);
+    }
+    if n > size
+        || input[input.len() - n..]
+            .iter()
+            .any(|&b| usize::from(b) != n)
+    {
+        return Err(PaddingError::InconsistentPadBytes);
+    }
+
+    Ok(input[..input.len() - n].to_vec())
+}
+
 /// Returns a cipher's block size.
-pub fn discover_block_size<F>(f: F) -> usize
-where
-    F: Fn(&[u8]) -> Vec<u8>,
-{
-    let base_len = f(&[0]).len();
-
-    for i in 2.. {
-        let len = f(&vec![0; i]).len();
+///
+/// The size is discovered by feeding the encryption oracle increasingly long inputs,
+/// until the length of the ciphertext changes.
+pub fn discover_block_size<O: EncryptionOracle>(oracle: &O) -> crate::Result<usize> {
+    let base_len = oracle.query(&[0])?.len();
+
+    for i in 2..=MAX_BLOCK_SIZE + 1 {
+        let len = oracle.query(&vec![0; i])?.len();
         if len != base_len {
-            return len - base_len;
+            return Ok(len - base_len);
         }
     }
-    panic!("Block size never changed!");
+
+    Err(Error::AttackFailed("Block size never changed"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::crypto::aes::ecb;
+    use crate::{
+        crypto::aes::ecb,
+        oracle::{FnOracle, Oracle},
+    };
 
     #[test]
     fn pkcs7_works() {
@@ -61,10 +120,47 @@ mod tests {
     }
 
     #[test]
-    fn discover_block_size_works() {
+    fn pkcs7_unpad_works() {
+        assert_eq!(pkcs7_unpad(b"0000\x04\x04\x04\x04", 4).unwrap(), b"0000");
+        assert_eq!(pkcs7_unpad(b"0000\x01", 5).unwrap(), b"0000");
+        assert_eq!(pkcs7_unpad(b"0000\x03\x03\x03", 7).unwrap(), b"0000");
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x04\x04\x04\x04", 16).unwrap(),
+            b"ICE ICE BABY"
+        );
+
+        for n in 1..=255u8 {
+            assert_eq!(
+                pkcs7_unpad(pkcs7(b"YELLOW SUBMARINE", n), n).unwrap(),
+                b"YELLOW SUBMARINE"
+            );
+        }
+    }
+
+    #[test]
+    fn pkcs7_unpad_fails() {
+        assert_eq!(pkcs7_unpad(b"", 4), Err(PaddingError::BadLength));
+        assert_eq!(pkcs7_unpad(b"000\x01", 8), Err(PaddingError::BadLength));
+        assert_eq!(pkcs7_unpad(b"0000\x00", 5), Err(PaddingError::ZeroPadByte));
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x05\x05\x05\x05", 16),
+            Err(PaddingError::InconsistentPadBytes)
+        );
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x01\x02\x03\x04", 16),
+            Err(PaddingError::InconsistentPadBytes)
+        );
         assert_eq!(
-            discover_block_size(|i| ecb::encrypt(i, b"YELLOW SUBMARINE", true).unwrap()),
-            16
+            pkcs7_unpad(b"0000\x05", 5),
+            Err(PaddingError::InconsistentPadBytes)
         );
     }
+
+    #[test]
+    fn discover_block_size_works() {
+        let oracle = FnOracle::new(|i: &[u8]| ecb::encrypt(i, b"YELLOW SUBMARINE", true));
+
+        assert_eq!(discover_block_size(&oracle).unwrap(), 16);
+        assert!(oracle.queries() <= 17);
+    }
 }
@@ -1,3 +1,9 @@
 /// This module contains the cryptographic functions used throughout the challenge.
 pub mod aes;
+pub mod bigint;
+pub mod cipher;
+pub mod dh;
+pub mod hash;
+pub mod hmac;
 pub mod misc;
+pub mod prng;
@@ -0,0 +1,444 @@
+//! The MT19937 Mersenne Twister pseudo-random number generator, in its 32-bit and 64-bit variants.
+//!
+//! Neither variant is cryptographically secure: its whole state can be recovered
+//! from enough consecutive outputs, see [`Mt19937::from_outputs`].
+//!
+//! [`Mt19937::from_outputs`]: struct.Mt19937.html#method.from_outputs
+
+use crate::crypto::misc;
+
+use rand::{Error, RngCore, SeedableRng};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const N: usize = 624;
+const M: usize = 397;
+const MATRIX_A: u32 = 0x9908_b0df;
+const UPPER_MASK: u32 = 0x8000_0000;
+const LOWER_MASK: u32 = 0x7fff_ffff;
+
+const N_64: usize = 312;
+const M_64: usize = 156;
+const MATRIX_A_64: u64 = 0xb502_6f5a_a966_19e9;
+const UPPER_MASK_64: u64 = 0xffff_ffff_8000_0000;
+const LOWER_MASK_64: u64 = 0x0000_0000_7fff_ffff;
+
+/// The 32-bit Mersenne Twister.
+#[derive(Clone)]
+pub struct Mt19937 {
+    state: [u32; N],
+    index: usize,
+}
+
+impl Mt19937 {
+    /// Creates a new generator initialized with the given seed.
+    pub fn new(seed: u32) -> Self {
+        let mut state = [0; N];
+
+        state[0] = seed;
+        for i in 1..N {
+            state[i] = 1_812_433_253u32
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 30))
+                .wrapping_add(i as u32);
+        }
+
+        Mt19937 { state, index: N }
+    }
+
+    /// Recreates a generator from `N` = 624 consecutive outputs.
+    ///
+    /// The returned generator produces the same outputs as the original one
+    /// from that point onward. Returns `None` if not enough outputs are provided.
+    pub fn from_outputs(outputs: &[u32]) -> Option<Self> {
+        if outputs.len() < N {
+            return None;
+        }
+
+        let mut state = [0; N];
+        for (s, &y) in state.iter_mut().zip(&outputs[outputs.len() - N..]) {
+            *s = untemper(y);
+        }
+
+        Some(Mt19937 { state, index: N })
+    }
+
+    /// Generates the next `N` words of the internal state.
+    fn twist(&mut self) {
+        for i in 0..N {
+            let y = (self.state[i] & UPPER_MASK) | (self.state[(i + 1) % N] & LOWER_MASK);
+            let mag = if y & 1 != 0 { MATRIX_A } else { 0 };
+
+            self.state[i] = self.state[(i + M) % N] ^ (y >> 1) ^ mag;
+        }
+
+        self.index = 0;
+    }
+
+    /// Returns the next 32-bit output of the generator.
+    pub fn extract(&mut self) -> u32 {
+        if self.index >= N {
+            self.twist();
+        }
+
+        let y = self.state[self.index];
+        self.index += 1;
+
+        temper(y)
+    }
+}
+
+impl RngCore for Mt19937 {
+    fn next_u32(&mut self) -> u32 {
+        self.extract()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let lo = u64::from(self.next_u32());
+        let hi = u64::from(self.next_u32());
+        (hi << 32) | lo
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            chunk.copy_from_slice(&self.next_u32().to_le_bytes()[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for Mt19937 {
+    type Seed = [u8; 4];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Mt19937::new(u32::from_le_bytes(seed))
+    }
+}
+
+/// The 64-bit Mersenne Twister.
+#[derive(Clone)]
+pub struct Mt19937_64 {
+    state: [u64; N_64],
+    index: usize,
+}
+
+impl Mt19937_64 {
+    /// Creates a new generator initialized with the given seed.
+    pub fn new(seed: u64) -> Self {
+        let mut state = [0; N_64];
+
+        state[0] = seed;
+        for i in 1..N_64 {
+            state[i] = 6_364_136_223_846_793_005u64
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 62))
+                .wrapping_add(i as u64);
+        }
+
+        Mt19937_64 { state, index: N_64 }
+    }
+
+    /// Recreates a generator from `N_64` = 312 consecutive outputs.
+    ///
+    /// The returned generator produces the same outputs as the original one
+    /// from that point onward. Returns `None` if not enough outputs are provided.
+    pub fn from_outputs(outputs: &[u64]) -> Option<Self> {
+        if outputs.len() < N_64 {
+            return None;
+        }
+
+        let mut state = [0; N_64];
+        for (s, &y) in state.iter_mut().zip(&outputs[outputs.len() - N_64..]) {
+            *s = untemper_64(y);
+        }
+
+        Some(Mt19937_64 { state, index: N_64 })
+    }
+
+    /// Generates the next `N_64` words of the internal state.
+    fn twist(&mut self) {
+        for i in 0..N_64 {
+            let x = (self.state[i] & UPPER_MASK_64) | (self.state[(i + 1) % N_64] & LOWER_MASK_64);
+            let mag = if x & 1 != 0 { MATRIX_A_64 } else { 0 };
+
+            self.state[i] = self.state[(i + M_64) % N_64] ^ (x >> 1) ^ mag;
+        }
+
+        self.index = 0;
+    }
+
+    /// Returns the next 64-bit output of the generator.
+    pub fn extract(&mut self) -> u64 {
+        if self.index >= N_64 {
+            self.twist();
+        }
+
+        let y = self.state[self.index];
+        self.index += 1;
+
+        temper_64(y)
+    }
+}
+
+impl RngCore for Mt19937_64 {
+    fn next_u32(&mut self) -> u32 {
+        self.extract() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.extract()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes()[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for Mt19937_64 {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Mt19937_64::new(u64::from_le_bytes(seed))
+    }
+
+    fn seed_from_u64(seed: u64) -> Self {
+        Mt19937_64::new(seed)
+    }
+}
+
+/// Applies the MT19937 tempering transform to a state word.
+pub fn temper(mut y: u32) -> u32 {
+    y ^= y >> 11;
+    y ^= (y << 7) & 0x9d2c_5680;
+    y ^= (y << 15) & 0xefc6_0000;
+    y ^ (y >> 18)
+}
+
+/// Inverts [`temper`](fn.temper.html), recovering a state word from an output.
+pub fn untemper(mut y: u32) -> u32 {
+    y = undo_right_shift(y, 18);
+    y = undo_left_shift(y, 15, 0xefc6_0000);
+    y = undo_left_shift(y, 7, 0x9d2c_5680);
+    undo_right_shift(y, 11)
+}
+
+/// Applies the MT19937-64 tempering transform to a state word.
+pub fn temper_64(mut y: u64) -> u64 {
+    y ^= (y >> 29) & 0x5555_5555_5555_5555;
+    y ^= (y << 17) & 0x71d6_7fff_eda6_0000;
+    y ^= (y << 37) & 0xfff7_eee0_0000_0000;
+    y ^ (y >> 43)
+}
+
+/// Inverts [`temper_64`](fn.temper_64.html), recovering a state word from an output.
+pub fn untemper_64(mut y: u64) -> u64 {
+    y = undo_right_shift_64(y, 43, !0);
+    y = undo_left_shift_64(y, 37, 0xfff7_eee0_0000_0000);
+    y = undo_left_shift_64(y, 17, 0x71d6_7fff_eda6_0000);
+    undo_right_shift_64(y, 29, 0x5555_5555_5555_5555)
+}
+
+/// Inverts `y ^= y >> shift`.
+fn undo_right_shift(y: u32, shift: u32) -> u32 {
+    // Each iteration recovers `shift` more of the most significant bits
+    (0..32 / shift).fold(y, |x, _| y ^ (x >> shift))
+}
+
+/// Inverts `y ^= (y << shift) & mask`.
+fn undo_left_shift(y: u32, shift: u32, mask: u32) -> u32 {
+    // Each iteration recovers `shift` more of the least significant bits
+    (0..32 / shift).fold(y, |x, _| y ^ ((x << shift) & mask))
+}
+
+/// Inverts `y ^= (y >> shift) & mask`.
+fn undo_right_shift_64(y: u64, shift: u32, mask: u64) -> u64 {
+    (0..64 / shift).fold(y, |x, _| y ^ ((x >> shift) & mask))
+}
+
+/// Inverts `y ^= (y << shift) & mask`.
+fn undo_left_shift_64(y: u64, shift: u32, mask: u64) -> u64 {
+    (0..64 / shift).fold(y, |x, _| y ^ ((x << shift) & mask))
+}
+
+/// Returns the current UNIX time, truncated to 32 bits.
+fn unix_timestamp() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as u32)
+        .unwrap_or(0)
+}
+
+/// Recovers the seed of an MT19937 generator seeded with a recent UNIX timestamp.
+///
+/// All timestamps in the last `window` seconds are tried, newest first,
+/// until one produces `output` as its first output.
+pub fn crack_timestamp_seed(output: u32, window: u32) -> Option<u32> {
+    let now = unix_timestamp();
+
+    (0..=window)
+        .map(|dt| now.wrapping_sub(dt))
+        .find(|&seed| Mt19937::new(seed).extract() == output)
+}
+
+/// Encrypts data by XORing it with the output of an MT19937 generator seeded with `seed`.
+pub fn stream_encrypt<I: AsRef<[u8]>>(input: I, seed: u16) -> Vec<u8> {
+    let input = input.as_ref();
+
+    let mut keystream = vec![0; input.len()];
+    Mt19937::new(u32::from(seed)).fill_bytes(&mut keystream);
+
+    misc::xor(input, &keystream)
+}
+
+/// Decrypts data encrypted by [`stream_encrypt`](fn.stream_encrypt.html).
+pub fn stream_decrypt<I: AsRef<[u8]>>(input: I, seed: u16) -> Vec<u8> {
+    stream_encrypt(input, seed)
+}
+
+/// Recovers the 16-bit seed of a ciphertext produced by [`stream_encrypt`]
+/// whose plaintext is known to end with `known`.
+///
+/// [`stream_encrypt`]: fn.stream_encrypt.html
+pub fn recover_stream_seed<I: AsRef<[u8]>>(ciphertext: I, known: &[u8]) -> Option<u16> {
+    let ciphertext = ciphertext.as_ref();
+
+    if known.len() > ciphertext.len() {
+        return None;
+    }
+
+    (0..=u16::MAX).find(|&seed| stream_decrypt(ciphertext, seed).ends_with(known))
+}
+
+/// Generates a password reset token of `len` bytes from MT19937 seeded with the current time.
+pub fn mt_token(len: usize) -> Vec<u8> {
+    let mut token = vec![0; len];
+    Mt19937::new(unix_timestamp()).fill_bytes(&mut token);
+    token
+}
+
+/// Returns whether `token` was generated by [`mt_token`] in the last `window` seconds.
+///
+/// [`mt_token`]: fn.mt_token.html
+pub fn is_mt_token<I: AsRef<[u8]>>(token: I, window: u32) -> bool {
+    let token = token.as_ref();
+    let now = unix_timestamp();
+
+    (0..=window).map(|dt| now.wrapping_sub(dt)).any(|seed| {
+        let mut expected = vec![0; token.len()];
+        Mt19937::new(seed).fill_bytes(&mut expected);
+        expected == token
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mt19937_reference_outputs() {
+        let mut rng = Mt19937::new(5489);
+
+        assert_eq!(rng.extract(), 3_499_211_612);
+        assert_eq!((0..9_999).fold(0, |_, _| rng.extract()), 4_123_659_995);
+    }
+
+    #[test]
+    fn mt19937_64_reference_outputs() {
+        let mut rng = Mt19937_64::new(5489);
+
+        assert_eq!(rng.extract(), 14_514_284_786_278_117_030);
+        assert_eq!(
+            (0..9_999).fold(0, |_, _| rng.extract()),
+            9_981_545_732_273_789_042
+        );
+    }
+
+    #[test]
+    fn untemper_inverts_temper() {
+        for _ in 0..1000 {
+            let y = rand::random();
+            assert_eq!(untemper(temper(y)), y);
+
+            let y = rand::random();
+            assert_eq!(untemper_64(temper_64(y)), y);
+        }
+    }
+
+    #[test]
+    fn mt19937_clone() {
+        let mut rng = Mt19937::new(rand::random());
+
+        // Skip some outputs so that we are not aligned with the state
+        for _ in 0..rand::random::<u16>() {
+            rng.extract();
+        }
+
+        let outputs = (0..N).map(|_| rng.extract()).collect::<Vec<_>>();
+        let mut clone = Mt19937::from_outputs(&outputs).unwrap();
+
+        for _ in 0..2 * N {
+            assert_eq!(clone.extract(), rng.extract());
+        }
+
+        assert!(Mt19937::from_outputs(&outputs[1..]).is_none());
+    }
+
+    #[test]
+    fn mt19937_64_clone() {
+        let mut rng = Mt19937_64::new(rand::random());
+
+        for _ in 0..rand::random::<u16>() {
+            rng.extract();
+        }
+
+        let outputs = (0..N_64).map(|_| rng.extract()).collect::<Vec<_>>();
+        let mut clone = Mt19937_64::from_outputs(&outputs).unwrap();
+
+        for _ in 0..2 * N_64 {
+            assert_eq!(clone.extract(), rng.extract());
+        }
+    }
+
+    #[test]
+    fn crack_timestamp_seed_works() {
+        let seed = unix_timestamp() - 500;
+        let output = Mt19937::new(seed).extract();
+
+        assert_eq!(crack_timestamp_seed(output, 1000), Some(seed));
+        assert_eq!(crack_timestamp_seed(output, 100), None);
+    }
+
+    #[test]
+    fn stream_roundtrip() {
+        let plaintext = b"The quick brown fox jumps over the lazy dog";
+        let ciphertext = stream_encrypt(&plaintext[..], 0xbeef);
+
+        assert_ne!(&ciphertext[..], &plaintext[..]);
+        assert_eq!(stream_decrypt(ciphertext, 0xbeef), &plaintext[..]);
+    }
+
+    #[test]
+    fn recover_stream_seed_works() {
+        let seed = rand::random();
+        let ciphertext = stream_encrypt(b"xyzAAAAAAAAAAAAAA", seed);
+
+        assert_eq!(
+            recover_stream_seed(ciphertext, b"AAAAAAAAAAAAAA"),
+            Some(seed)
+        );
+    }
+
+    #[test]
+    fn is_mt_token_works() {
+        assert!(is_mt_token(mt_token(16), 10));
+        assert!(!is_mt_token(rand::random::<[u8; 16]>(), 10));
+    }
+}
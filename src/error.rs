@@ -0,0 +1,106 @@
+use crate::{crypto::misc::PaddingError, oracle::OracleError};
+
+use std::{error, fmt, io, string::FromUtf8Error};
+
+/// Error type used across the crate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// Invalid hex-encoded data.
+    Hex(hex::FromHexError),
+    /// Invalid Base64-encoded data.
+    Base64(base64::DecodeError),
+    /// Data that was expected to be UTF-8 is not.
+    Utf8(FromUtf8Error),
+    /// An I/O operation failed.
+    Io(io::ErrorKind),
+    /// The key length is not supported by the cipher.
+    InvalidKeyLength(usize),
+    /// The IV (or nonce) length is not supported by the cipher.
+    InvalidIvLength(usize),
+    /// The input length is not a multiple of the cipher's block size.
+    InvalidInputLength(usize),
+    /// The decrypted plaintext is not correctly padded.
+    Padding(PaddingError),
+    /// The decrypted plaintext contains invalid characters. The plaintext is returned.
+    InvalidPlaintext(Vec<u8>),
+    /// An oracle refused to answer a query.
+    Oracle(OracleError),
+    /// A party deviated from the protocol.
+    Protocol(&'static str),
+    /// Encoded data could not be parsed.
+    Parse(&'static str),
+    /// An argument is outside of its valid range.
+    InvalidArgument(&'static str),
+    /// An attack did not succeed.
+    AttackFailed(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Hex(e) => write!(f, "hex decoding error: {}", e),
+            Error::Base64(e) => write!(f, "base64 decoding error: {}", e),
+            Error::Utf8(e) => write!(f, "UTF-8 error: {}", e),
+            Error::Io(kind) => write!(f, "I/O error: {:?}", kind),
+            Error::InvalidKeyLength(n) => write!(f, "invalid key length: {}", n),
+            Error::InvalidIvLength(n) => write!(f, "invalid IV length: {}", n),
+            Error::InvalidInputLength(n) => write!(f, "invalid input length: {}", n),
+            Error::Padding(e) => write!(f, "padding error: {}", e),
+            Error::InvalidPlaintext(pt) => write!(f, "invalid plaintext: {}", hex::encode(pt)),
+            Error::Oracle(e) => write!(f, "oracle error: {}", e),
+            Error::Protocol(s) => write!(f, "protocol error: {}", s),
+            Error::Parse(s) => write!(f, "parse error: {}", s),
+            Error::InvalidArgument(s) => write!(f, "invalid argument: {}", s),
+            Error::AttackFailed(s) => write!(f, "attack failed: {}", s),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Hex(e) => Some(e),
+            Error::Base64(e) => Some(e),
+            Error::Utf8(e) => Some(e),
+            Error::Padding(e) => Some(e),
+            Error::Oracle(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<hex::FromHexError> for Error {
+    fn from(e: hex::FromHexError) -> Self {
+        Error::Hex(e)
+    }
+}
+
+impl From<base64::DecodeError> for Error {
+    fn from(e: base64::DecodeError) -> Self {
+        Error::Base64(e)
+    }
+}
+
+impl From<FromUtf8Error> for Error {
+    fn from(e: FromUtf8Error) -> Self {
+        Error::Utf8(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e.kind())
+    }
+}
+
+impl From<PaddingError> for Error {
+    fn from(e: PaddingError) -> Self {
+        Error::Padding(e)
+    }
+}
+
+impl From<OracleError> for Error {
+    fn from(e: OracleError) -> Self {
+        Error::Oracle(e)
+    }
+}
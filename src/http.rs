@@ -0,0 +1,222 @@
+//! A minimal HTTP server, listening on localhost, that verifies file signatures.
+//!
+//! It stands in for the web application of Set 4 Challenges 31 and 32:
+//! a request for `/test?file=foo&signature=46b4ec586117154dacd49d664e5d63fdc88efb51`
+//! succeeds if the signature is the HMAC-SHA1 of the file name, and fails otherwise.
+//! Signatures are checked with [`insecure_compare`], leaking timing information.
+//!
+//! [`insecure_compare`]: ../crypto/hmac/fn.insecure_compare.html
+
+use crate::{
+    crypto::{
+        hash::sha1::{self, Sha1},
+        hmac::{hmac, insecure_compare},
+    },
+    utils, Error, Result,
+};
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// A signature-verifying server running on a background thread.
+///
+/// The server is shut down when dropped.
+pub struct Server {
+    addr: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    /// Starts a server on an ephemeral localhost port, verifying signatures
+    /// under `key` and sleeping for `delay` after each matching signature byte.
+    pub fn spawn<K: AsRef<[u8]>>(key: K, delay: Duration) -> Result<Self> {
+        Server::spawn_truncated(key, delay, sha1::OUTPUT_SIZE)
+    }
+
+    /// Starts a server like [`spawn`], but only using the first `mac_len` bytes
+    /// of the HMAC as signature.
+    ///
+    /// [`spawn`]: #method.spawn
+    pub fn spawn_truncated<K: AsRef<[u8]>>(
+        key: K,
+        delay: Duration,
+        mac_len: usize,
+    ) -> Result<Self> {
+        if mac_len == 0 || mac_len > sha1::OUTPUT_SIZE {
+            return Err(Error::InvalidArgument("Invalid signature length"));
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+
+        let key = key.as_ref().to_vec();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let shutdown = shutdown.clone();
+
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    // A misbehaving client must not bring down the server
+                    if let Ok(stream) = stream {
+                        let _ = serve(stream, &key, delay, mac_len);
+                    }
+                }
+            })
+        };
+
+        Ok(Server {
+            addr,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+
+        // Wake up the listener so that it notices the shutdown
+        let _ = TcpStream::connect(self.addr);
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Answers a single request.
+fn serve(mut stream: TcpStream, key: &[u8], delay: Duration, mac_len: usize) -> Result<()> {
+    let mut reader = BufReader::new(&stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Skip the headers
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let status = match parse_request(&request_line) {
+        Some((file, signature)) => {
+            let mac = hmac::<Sha1, _, _>(key, file);
+
+            if insecure_compare(&mac[..mac_len], &signature, delay) {
+                "200 OK"
+            } else {
+                "500 Internal Server Error"
+            }
+        }
+        None => "400 Bad Request",
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        status
+    )?;
+
+    Ok(())
+}
+
+/// Extracts the file name and the decoded signature from a request line
+/// of the form `GET /test?file=...&signature=... HTTP/1.1`.
+fn parse_request(request_line: &str) -> Option<(String, Vec<u8>)> {
+    let mut parts = request_line.split_whitespace();
+
+    if parts.next()? != "GET" {
+        return None;
+    }
+
+    let target = parts.next()?;
+    let query = target.strip_prefix("/test?")?;
+    let params = utils::parse_kv_encoded(query).ok()?;
+
+    Some((
+        params.get("file")?.clone(),
+        hex::decode(params.get("signature")?).ok()?,
+    ))
+}
+
+/// Asks the server at `addr` whether `signature` is valid for `file`.
+pub fn verify(addr: SocketAddr, file: &str, signature: &[u8]) -> Result<bool> {
+    let mut stream = TcpStream::connect(addr)?;
+
+    write!(
+        stream,
+        "GET /test?file={}&signature={} HTTP/1.1\r\nHost: {}\r\n\r\n",
+        file,
+        hex::encode(signature),
+        addr
+    )?;
+
+    let mut status_line = String::new();
+    BufReader::new(&stream).read_line(&mut status_line)?;
+
+    Ok(status_line.split_whitespace().nth(1) == Some("200"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_verifies_signatures() {
+        let server = Server::spawn(b"YELLOW SUBMARINE", Duration::from_millis(0)).unwrap();
+        let signature = hmac::<Sha1, _, _>(b"YELLOW SUBMARINE", "foo");
+
+        assert!(verify(server.addr(), "foo", &signature).unwrap());
+        assert!(!verify(server.addr(), "bar", &signature).unwrap());
+        assert!(!verify(server.addr(), "foo", &signature[1..]).unwrap());
+    }
+
+    #[test]
+    fn server_verifies_truncated_signatures() {
+        let server =
+            Server::spawn_truncated(b"YELLOW SUBMARINE", Duration::from_millis(0), 4).unwrap();
+        let signature = hmac::<Sha1, _, _>(b"YELLOW SUBMARINE", "foo");
+
+        assert!(verify(server.addr(), "foo", &signature[..4]).unwrap());
+        assert!(!verify(server.addr(), "foo", &signature).unwrap());
+        assert!(
+            Server::spawn_truncated(b"YELLOW SUBMARINE", Duration::from_millis(0), 21).is_err()
+        );
+    }
+
+    #[test]
+    fn parse_request_works() {
+        assert_eq!(
+            parse_request("GET /test?file=foo&signature=0aff HTTP/1.1\r\n"),
+            Some(("foo".to_string(), vec![0x0a, 0xff]))
+        );
+        assert_eq!(parse_request("GET /test?file=foo HTTP/1.1\r\n"), None);
+        assert_eq!(
+            parse_request("GET /test?file=foo&signature=xyz HTTP/1.1\r\n"),
+            None
+        );
+        assert_eq!(
+            parse_request("POST /test?file=foo&signature=00 HTTP/1.1\r\n"),
+            None
+        );
+    }
+}
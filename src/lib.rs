@@ -1,9 +1,17 @@
-use std::{error::Error, result};
+use std::result;
 
+pub mod attacks;
 pub mod crypto;
+mod error;
+pub mod http;
+pub mod network;
+pub mod oracle;
+pub mod protocols;
 pub mod sets;
 pub mod text;
 pub mod utils;
 
+pub use error::Error;
+
 /// Result type used across the module.
-type Result<T> = result::Result<T, Box<dyn Error>>;
+pub type Result<T> = result::Result<T, Error>;
@@ -0,0 +1,207 @@
+//! A simulated network, running protocol parties as threads within the process.
+//!
+//! Parties talk through [`Endpoint`]s, which can be connected either directly
+//! or through a man-in-the-middle that can read, drop, rewrite and inject messages.
+//!
+//! [`Endpoint`]: struct.Endpoint.html
+
+use crate::{Error, Result};
+
+use std::{
+    io,
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+};
+
+/// One end of a bidirectional link carrying messages of type `M`.
+pub struct Endpoint<M> {
+    tx: Sender<M>,
+    rx: Receiver<M>,
+}
+
+impl<M> Endpoint<M> {
+    /// Sends a message to the other end of the link.
+    ///
+    /// Returns an error if the other end has hung up.
+    pub fn send(&self, message: M) -> Result<()> {
+        self.tx
+            .send(message)
+            .map_err(|_| Error::Io(io::ErrorKind::ConnectionAborted))
+    }
+
+    /// Waits for a message from the other end of the link.
+    ///
+    /// Returns an error if the other end has hung up.
+    pub fn recv(&self) -> Result<M> {
+        self.rx
+            .recv()
+            .map_err(|_| Error::Io(io::ErrorKind::ConnectionAborted))
+    }
+}
+
+/// Creates a bidirectional link, returning its two ends.
+pub fn link<M>() -> (Endpoint<M>, Endpoint<M>) {
+    let (tx_a, rx_b) = channel();
+    let (tx_b, rx_a) = channel();
+
+    (
+        Endpoint { tx: tx_a, rx: rx_a },
+        Endpoint { tx: tx_b, rx: rx_b },
+    )
+}
+
+/// A man-in-the-middle sitting between parties A and B.
+pub struct Mitm<M> {
+    /// The endpoint connected to A.
+    pub a: Endpoint<M>,
+    /// The endpoint connected to B.
+    pub b: Endpoint<M>,
+}
+
+impl<M> Mitm<M> {
+    /// Receives a message from A and forwards it to B after passing it through `f`.
+    ///
+    /// The message is dropped if `f` returns `None`.
+    pub fn relay_to_b<F: FnOnce(M) -> Option<M>>(&self, f: F) -> Result<()> {
+        match f(self.a.recv()?) {
+            Some(message) => self.b.send(message),
+            None => Ok(()),
+        }
+    }
+
+    /// Receives a message from B and forwards it to A after passing it through `f`.
+    ///
+    /// The message is dropped if `f` returns `None`.
+    pub fn relay_to_a<F: FnOnce(M) -> Option<M>>(&self, f: F) -> Result<()> {
+        match f(self.b.recv()?) {
+            Some(message) => self.a.send(message),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Runs parties A and B on their own threads, connected directly.
+///
+/// Returns the results of both parties.
+pub fn run<M, A, B, RA, RB>(alice: A, bob: B) -> (Result<RA>, Result<RB>)
+where
+    M: Send + 'static,
+    A: FnOnce(Endpoint<M>) -> Result<RA> + Send + 'static,
+    B: FnOnce(Endpoint<M>) -> Result<RB> + Send + 'static,
+    RA: Send + 'static,
+    RB: Send + 'static,
+{
+    let (a, b) = link();
+
+    let alice = thread::spawn(move || alice(a));
+    let bob = thread::spawn(move || bob(b));
+
+    (join(alice), join(bob))
+}
+
+/// Runs parties A and B on their own threads, with all of their traffic
+/// going through a man-in-the-middle running on the current thread.
+///
+/// Returns the results of the three parties.
+pub fn run_with_mitm<M, A, B, F, RA, RB, RM>(
+    alice: A,
+    bob: B,
+    mitm: F,
+) -> (Result<RA>, Result<RB>, Result<RM>)
+where
+    M: Send + 'static,
+    A: FnOnce(Endpoint<M>) -> Result<RA> + Send + 'static,
+    B: FnOnce(Endpoint<M>) -> Result<RB> + Send + 'static,
+    F: FnOnce(Mitm<M>) -> Result<RM>,
+    RA: Send + 'static,
+    RB: Send + 'static,
+{
+    let (a, mitm_a) = link();
+    let (b, mitm_b) = link();
+
+    let alice = thread::spawn(move || alice(a));
+    let bob = thread::spawn(move || bob(b));
+
+    // The MITM hangs up on the parties before we wait for them, in case it gives up early
+    let intercepted = mitm(Mitm {
+        a: mitm_a,
+        b: mitm_b,
+    });
+
+    (join(alice), join(bob), intercepted)
+}
+
+/// Waits for a party to finish, turning a panic into an error.
+fn join<R>(handle: thread::JoinHandle<Result<R>>) -> Result<R> {
+    handle
+        .join()
+        .unwrap_or(Err(Error::Protocol("A party panicked")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direct_link() {
+        let (a, b) = run(
+            |ep: Endpoint<u32>| {
+                ep.send(1)?;
+                ep.recv()
+            },
+            |ep: Endpoint<u32>| {
+                let n = ep.recv()?;
+                ep.send(n + 1)?;
+                Ok(n)
+            },
+        );
+
+        assert_eq!((a.unwrap(), b.unwrap()), (2, 1));
+    }
+
+    #[test]
+    fn mitm_can_rewrite_drop_and_inject() {
+        let (a, b, seen) = run_with_mitm(
+            |ep: Endpoint<u32>| {
+                ep.send(1)?;
+                ep.send(2)?;
+                ep.recv()
+            },
+            |ep: Endpoint<u32>| ep.recv(),
+            |mitm| {
+                let mut seen = vec![];
+
+                mitm.relay_to_b(|n| {
+                    seen.push(n);
+                    Some(n * 10)
+                })?;
+                mitm.relay_to_b(|n| {
+                    seen.push(n);
+                    None
+                })?;
+                mitm.a.send(42)?;
+
+                Ok(seen)
+            },
+        );
+
+        assert_eq!(
+            (a.unwrap(), b.unwrap(), seen.unwrap()),
+            (42, 10, vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn hang_up_is_an_error() {
+        let (a, b) = run(
+            |ep: Endpoint<u32>| ep.recv(),
+            |ep: Endpoint<u32>| {
+                drop(ep);
+                Ok(())
+            },
+        );
+
+        assert_eq!(a.unwrap_err(), Error::Io(io::ErrorKind::ConnectionAborted));
+        assert!(b.is_ok());
+    }
+}
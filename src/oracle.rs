@@ -0,0 +1,181 @@
+//! Oracles queried by the attacks in this crate.
+//!
+//! Every oracle is fallible, keeps track of how many queries it answered
+//! and can optionally refuse to answer once a query budget is exhausted.
+
+use crate::{Error, Result};
+
+use std::{cell::Cell, error, fmt, time::Duration};
+
+/// Error returned by an oracle that refuses to answer a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OracleError {
+    /// The oracle already answered the maximum allowed number of queries.
+    BudgetExhausted(usize),
+}
+
+impl fmt::Display for OracleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OracleError::BudgetExhausted(n) => write!(f, "query budget of {} exhausted", n),
+        }
+    }
+}
+
+impl error::Error for OracleError {}
+
+/// An oracle answering queries of type `Q`.
+pub trait Oracle<Q> {
+    /// The type of the oracle's answers.
+    type Answer;
+
+    /// Queries the oracle.
+    fn query(&self, query: Q) -> Result<Self::Answer>;
+
+    /// Returns the number of queries made so far.
+    fn queries(&self) -> usize;
+}
+
+impl<Q, O: Oracle<Q> + ?Sized> Oracle<Q> for &O {
+    type Answer = O::Answer;
+
+    fn query(&self, query: Q) -> Result<Self::Answer> {
+        (**self).query(query)
+    }
+
+    fn queries(&self) -> usize {
+        (**self).queries()
+    }
+}
+
+/// An oracle returning the encryption of its input.
+pub trait EncryptionOracle: for<'a> Oracle<&'a [u8], Answer = Vec<u8>> {}
+
+impl<O: ?Sized> EncryptionOracle for O where O: for<'a> Oracle<&'a [u8], Answer = Vec<u8>> {}
+
+/// A query to a [`PaddingOracle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaddingQuery<'a> {
+    /// The IV to decrypt with.
+    pub iv: &'a [u8],
+    /// The ciphertext whose padding is checked.
+    pub ciphertext: &'a [u8],
+}
+
+/// An oracle taking an IV and a ciphertext, and returning whether the padding is valid.
+pub trait PaddingOracle: for<'a> Oracle<PaddingQuery<'a>, Answer = bool> {}
+
+impl<O: ?Sized> PaddingOracle for O where O: for<'a> Oracle<PaddingQuery<'a>, Answer = bool> {}
+
+/// A query to a [`MacOracle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MacQuery<'a> {
+    /// The authenticated message.
+    pub message: &'a [u8],
+    /// The MAC to check against the message.
+    pub mac: &'a [u8],
+}
+
+/// An oracle taking a message and a MAC, and returning whether the MAC is valid.
+pub trait MacOracle: for<'a> Oracle<MacQuery<'a>, Answer = bool> {}
+
+impl<O: ?Sized> MacOracle for O where O: for<'a> Oracle<MacQuery<'a>, Answer = bool> {}
+
+/// An oracle taking an offset and a new plaintext, and returning the edited ciphertext.
+pub trait EditOracle: for<'a> Oracle<(usize, &'a [u8]), Answer = Vec<u8>> {}
+
+impl<O: ?Sized> EditOracle for O where O: for<'a> Oracle<(usize, &'a [u8]), Answer = Vec<u8>> {}
+
+/// An oracle taking a MAC, and returning whether it is valid along with the time it took to answer.
+pub trait TimingOracle: for<'a> Oracle<&'a [u8], Answer = (bool, Duration)> {}
+
+impl<O: ?Sized> TimingOracle for O where O: for<'a> Oracle<&'a [u8], Answer = (bool, Duration)> {}
+
+/// An oracle answering queries through a closure.
+pub struct FnOracle<F> {
+    f: F,
+    queries: Cell<usize>,
+    budget: Option<usize>,
+}
+
+impl<F> FnOracle<F> {
+    /// Creates an oracle without a query budget.
+    pub fn new(f: F) -> Self {
+        FnOracle {
+            f,
+            queries: Cell::new(0),
+            budget: None,
+        }
+    }
+
+    /// Limits the number of queries that the oracle will answer.
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+}
+
+impl<Q, A, F> Oracle<Q> for FnOracle<F>
+where
+    F: Fn(Q) -> Result<A>,
+{
+    type Answer = A;
+
+    fn query(&self, query: Q) -> Result<A> {
+        if let Some(budget) = self.budget {
+            if self.queries.get() >= budget {
+                return Err(Error::Oracle(OracleError::BudgetExhausted(budget)));
+            }
+        }
+
+        self.queries.set(self.queries.get() + 1);
+
+        (self.f)(query)
+    }
+
+    fn queries(&self) -> usize {
+        self.queries.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_are_counted() {
+        let oracle = FnOracle::new(|i: &[u8]| Ok(i.to_vec()));
+
+        for i in 0..10 {
+            assert_eq!(oracle.queries(), i);
+            assert_eq!(oracle.query(b"foo").unwrap(), b"foo");
+        }
+    }
+
+    #[test]
+    fn budget_is_enforced() {
+        let oracle = FnOracle::new(|i: &[u8]| Ok(i.len())).with_budget(3);
+
+        for _ in 0..3 {
+            assert!(oracle.query(b"foo").is_ok());
+        }
+
+        assert_eq!(
+            oracle.query(b"foo").unwrap_err(),
+            Error::Oracle(OracleError::BudgetExhausted(3))
+        );
+        assert_eq!(oracle.queries(), 3);
+    }
+
+    #[test]
+    fn errors_are_propagated() {
+        let oracle =
+            FnOracle::new(|_: &[u8]| -> Result<()> { Err(Error::AttackFailed("Failure")) });
+
+        assert_eq!(
+            oracle.query(b"foo").unwrap_err(),
+            Error::AttackFailed("Failure")
+        );
+        assert_eq!(oracle.queries(), 1);
+    }
+}
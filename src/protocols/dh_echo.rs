@@ -0,0 +1,195 @@
+//! The Diffie-Hellman echo protocol from Set 5 Challenges 34 and 35.
+//!
+//! A agrees on a key with B, then sends B a message encrypted under it.
+//! B decrypts the message and echoes it back, re-encrypted under the same key.
+//!
+//! Two variants of the key agreement exist: in the first one, A sends the group
+//! parameters along with its public key; in the second one, the group is negotiated
+//! and acknowledged first, and the public keys are exchanged afterwards.
+
+use crate::{
+    crypto::{
+        bigint::BigUint,
+        dh::{self, DhGroup},
+    },
+    network::Endpoint,
+    Error, Result,
+};
+
+/// A message of the protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// The group parameters, along with the sender's public key.
+    Params {
+        p: BigUint,
+        g: BigUint,
+        public: BigUint,
+    },
+    /// The group parameters alone, in the negotiated variant.
+    Group { p: BigUint, g: BigUint },
+    /// Acknowledges the group parameters, in the negotiated variant.
+    Ack,
+    /// The sender's public key.
+    PublicKey(BigUint),
+    /// A message encrypted with [`dh::encrypt`].
+    ///
+    /// [`dh::encrypt`]: ../../crypto/dh/fn.encrypt.html
+    Data(Vec<u8>),
+}
+
+/// Receives a public key, failing on any other message.
+fn recv_public_key(ep: &Endpoint<Message>) -> Result<BigUint> {
+    match ep.recv()? {
+        Message::PublicKey(public) => Ok(public),
+        _ => Err(Error::Protocol("Expected a public key")),
+    }
+}
+
+/// Receives encrypted data, failing on any other message.
+fn recv_data(ep: &Endpoint<Message>) -> Result<Vec<u8>> {
+    match ep.recv()? {
+        Message::Data(data) => Ok(data),
+        _ => Err(Error::Protocol("Expected encrypted data")),
+    }
+}
+
+/// Sends `message` under the shared secret and returns the echo, as the initiator.
+fn send_and_check_echo(
+    ep: &Endpoint<Message>,
+    secret: &BigUint,
+    message: &[u8],
+) -> Result<Vec<u8>> {
+    ep.send(Message::Data(dh::encrypt(secret, message)?))?;
+
+    let echo = dh::decrypt(secret, recv_data(ep)?)?;
+    if echo != message {
+        return Err(Error::Protocol("Echo does not match the message"));
+    }
+
+    Ok(echo)
+}
+
+/// Receives a message under the shared secret and echoes it back, as the responder.
+fn echo(ep: &Endpoint<Message>, secret: &BigUint) -> Result<Vec<u8>> {
+    let message = dh::decrypt(secret, recv_data(ep)?)?;
+    ep.send(Message::Data(dh::encrypt(secret, &message)?))?;
+
+    Ok(message)
+}
+
+/// Runs the initiator (A), sending `message` to the responder.
+///
+/// Returns the echoed message, which is checked against the original.
+pub fn initiator(ep: Endpoint<Message>, group: DhGroup, message: &[u8]) -> Result<Vec<u8>> {
+    let keypair = group.generate_keypair();
+
+    ep.send(Message::Params {
+        p: group.p.clone(),
+        g: group.g.clone(),
+        public: keypair.public.clone(),
+    })?;
+
+    let secret = group.shared_secret(&keypair, &recv_public_key(&ep)?);
+
+    send_and_check_echo(&ep, &secret, message)
+}
+
+/// Runs the responder (B), using the parameters sent by the initiator.
+///
+/// Returns the message received from the initiator.
+pub fn responder(ep: Endpoint<Message>) -> Result<Vec<u8>> {
+    let (group, peer_public) = match ep.recv()? {
+        Message::Params { p, g, public } => (DhGroup::new(p, g, None), public),
+        _ => return Err(Error::Protocol("Expected group parameters")),
+    };
+
+    let keypair = group.generate_keypair();
+    ep.send(Message::PublicKey(keypair.public.clone()))?;
+
+    echo(&ep, &group.shared_secret(&keypair, &peer_public))
+}
+
+/// Runs the initiator (A) of the negotiated variant, sending `message` to the responder.
+///
+/// Returns the echoed message, which is checked against the original.
+pub fn negotiating_initiator(
+    ep: Endpoint<Message>,
+    group: DhGroup,
+    message: &[u8],
+) -> Result<Vec<u8>> {
+    ep.send(Message::Group {
+        p: group.p.clone(),
+        g: group.g.clone(),
+    })?;
+
+    if ep.recv()? != Message::Ack {
+        return Err(Error::Protocol("Expected an acknowledgment"));
+    }
+
+    let keypair = group.generate_keypair();
+    ep.send(Message::PublicKey(keypair.public.clone()))?;
+
+    let secret = group.shared_secret(&keypair, &recv_public_key(&ep)?);
+
+    send_and_check_echo(&ep, &secret, message)
+}
+
+/// Runs the responder (B) of the negotiated variant.
+///
+/// Returns the message received from the initiator.
+pub fn negotiating_responder(ep: Endpoint<Message>) -> Result<Vec<u8>> {
+    let group = match ep.recv()? {
+        Message::Group { p, g } => DhGroup::new(p, g, None),
+        _ => return Err(Error::Protocol("Expected group parameters")),
+    };
+
+    ep.send(Message::Ack)?;
+
+    let peer_public = recv_public_key(&ep)?;
+
+    let keypair = group.generate_keypair();
+    ep.send(Message::PublicKey(keypair.public.clone()))?;
+
+    echo(&ep, &group.shared_secret(&keypair, &peer_public))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network;
+
+    #[test]
+    fn echo_protocol() {
+        let (echoed, received) = network::run(
+            |ep| initiator(ep, DhGroup::nist(), b"Hello, Bob!"),
+            responder,
+        );
+
+        assert_eq!(echoed.unwrap(), b"Hello, Bob!");
+        assert_eq!(received.unwrap(), b"Hello, Bob!");
+    }
+
+    #[test]
+    fn negotiated_echo_protocol() {
+        let (echoed, received) = network::run(
+            |ep| negotiating_initiator(ep, DhGroup::nist(), b"Hello, Bob!"),
+            negotiating_responder,
+        );
+
+        assert_eq!(echoed.unwrap(), b"Hello, Bob!");
+        assert_eq!(received.unwrap(), b"Hello, Bob!");
+    }
+
+    #[test]
+    fn unexpected_messages_are_rejected() {
+        let (_, received) = network::run(
+            |ep| negotiating_initiator(ep, DhGroup::nist(), b"Hello, Bob!"),
+            responder,
+        );
+
+        assert_eq!(
+            received.unwrap_err(),
+            Error::Protocol("Expected group parameters")
+        );
+    }
+}
@@ -0,0 +1,3 @@
+/// This module contains protocols run between parties over the simulated network.
+pub mod dh_echo;
+pub mod srp;
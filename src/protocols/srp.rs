@@ -0,0 +1,448 @@
+//! Secure Remote Password (SRP-6a), as described in Set 5 Challenge 36,
+//! and its simplified variant from Challenge 38.
+//!
+//! Unlike Challenge 36, which uses `k = 3` as in the original SRP-6, the multiplier
+//! is derived from the group as `k = H(N || PAD(g))`. The private key is still
+//! `x = H(salt || password)`, as in the challenge.
+//!
+//! The client and the server are modelled as state machines: each incoming message
+//! is fed to [`Client::handle`] or [`Server::handle`], which produce the reply.
+//! [`run_client`] and [`run_server`] drive them over the simulated network.
+//!
+//! [`Client::handle`]: struct.Client.html#method.handle
+//! [`Server::handle`]: struct.Server.html#method.handle
+//! [`run_client`]: fn.run_client.html
+//! [`run_server`]: fn.run_server.html
+
+use crate::{
+    crypto::{
+        bigint::{self, BigUint},
+        dh::{DhGroup, Keypair},
+        hash::sha256::{self, Sha256},
+        hmac::hmac,
+    },
+    network::Endpoint,
+    Error, Result,
+};
+
+use std::collections::HashMap;
+
+/// The variants of the protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// `B = k * v + g^b mod N`, and `u = H(A || B)`.
+    Standard,
+    /// `B = g^b mod N`, and `u` is a random 128-bit number sent by the server.
+    ///
+    /// Since neither depends on the verifier, a fake server can mount
+    /// an offline dictionary attack against the client's password.
+    Simplified,
+}
+
+/// The public parameters of SRP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrpParams {
+    /// The group, with modulus `N` and generator `g`.
+    pub group: DhGroup,
+    /// The multiplier parameter.
+    pub k: BigUint,
+    /// The variant of the protocol.
+    pub variant: Variant,
+}
+
+impl SrpParams {
+    /// The parameters from Challenge 36: the NIST group, with the SRP-6a multiplier.
+    pub fn nist() -> Self {
+        let group = DhGroup::nist();
+
+        SrpParams {
+            k: multiplier(&group),
+            group,
+            variant: Variant::Standard,
+        }
+    }
+
+    /// The parameters from Challenge 38: the NIST group, with the simplified variant.
+    pub fn simplified() -> Self {
+        SrpParams {
+            variant: Variant::Simplified,
+            ..SrpParams::nist()
+        }
+    }
+}
+
+/// A message of the protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// Sent by the client: its identity and public key `A`.
+    Hello { email: String, public: BigUint },
+    /// Sent by the server: the salt, its public key `B`
+    /// and, in the simplified variant, the scrambling parameter `u`.
+    Challenge {
+        salt: Vec<u8>,
+        public: BigUint,
+        u: Option<BigUint>,
+    },
+    /// Sent by the client: proof of knowledge of the session key.
+    Proof(Vec<u8>),
+    /// Sent by the server: whether the client is authenticated.
+    Result(bool),
+}
+
+/// Hashes the concatenation of the inputs with SHA-256, and interprets it as an integer.
+fn hash_to_int(inputs: &[&[u8]]) -> BigUint {
+    bigint::from_bytes(sha256::digest(inputs.concat()))
+}
+
+/// Computes the SRP-6a multiplier parameter `k = H(N || PAD(g))`,
+/// where `g` is left-padded with zeros to the length of `N`.
+fn multiplier(group: &DhGroup) -> BigUint {
+    let n = bigint::to_bytes(&group.p);
+    let g = bigint::to_bytes(&group.g);
+    let pad = vec![0; n.len().saturating_sub(g.len())];
+
+    hash_to_int(&[&n, &pad, &g])
+}
+
+/// Computes the private key `x = H(salt || password)`.
+pub fn private_key(salt: &[u8], password: &[u8]) -> BigUint {
+    hash_to_int(&[salt, password])
+}
+
+/// Computes the scrambling parameter `u = H(A || B)`.
+fn scrambler(a: &BigUint, b: &BigUint) -> BigUint {
+    hash_to_int(&[&bigint::to_bytes(a), &bigint::to_bytes(b)])
+}
+
+/// Derives the session key `K = H(S)` from the shared secret.
+pub fn session_key(secret: &BigUint) -> [u8; sha256::OUTPUT_SIZE] {
+    sha256::digest(bigint::to_bytes(secret))
+}
+
+/// Computes the proof of knowledge of the session key, `HMAC-SHA256(K, salt)`.
+pub fn proof(key: &[u8], salt: &[u8]) -> Vec<u8> {
+    hmac::<Sha256, _, _>(key, salt).to_vec()
+}
+
+/// The states of a [`Client`](struct.Client.html).
+#[derive(Debug, Clone)]
+enum ClientState {
+    Start,
+    AwaitingChallenge(Keypair),
+    AwaitingResult,
+    Done(bool),
+}
+
+/// The client side of SRP.
+#[derive(Debug, Clone)]
+pub struct Client {
+    params: SrpParams,
+    email: String,
+    password: Vec<u8>,
+    state: ClientState,
+}
+
+impl Client {
+    /// Creates a client that will log in with the given credentials.
+    pub fn new<E, P>(params: SrpParams, email: E, password: P) -> Self
+    where
+        E: Into<String>,
+        P: AsRef<[u8]>,
+    {
+        Client {
+            params,
+            email: email.into(),
+            password: password.as_ref().to_vec(),
+            state: ClientState::Start,
+        }
+    }
+
+    /// Starts the protocol, returning the first message to send.
+    pub fn start(&mut self) -> Result<Message> {
+        match self.state {
+            ClientState::Start => {
+                let keypair = self.params.group.generate_keypair();
+                let public = keypair.public.clone();
+
+                self.state = ClientState::AwaitingChallenge(keypair);
+
+                Ok(Message::Hello {
+                    email: self.email.clone(),
+                    public,
+                })
+            }
+            _ => Err(Error::Protocol("Client already started")),
+        }
+    }
+
+    /// Handles a message from the server, returning the reply, if any.
+    pub fn handle(&mut self, message: Message) -> Result<Option<Message>> {
+        match (&self.state, message) {
+            (ClientState::AwaitingChallenge(keypair), Message::Challenge { salt, public, u }) => {
+                let SrpParams { group, k, variant } = &self.params;
+                let n = &group.p;
+
+                let x = private_key(&salt, &self.password);
+
+                // S = (B - k * g^x) ^ (a + u * x) mod N, or S = B ^ (a + u * x) mod N
+                let (base, u) = match (variant, u) {
+                    (Variant::Standard, None) => {
+                        let kgx = k * bigint::modexp(&group.g, &x, n) % n;
+                        let u = scrambler(&keypair.public, &public);
+
+                        ((&public % n + n - kgx) % n, u)
+                    }
+                    (Variant::Simplified, Some(u)) => (public, u),
+                    _ => return Err(Error::Protocol("Unexpected scrambling parameter")),
+                };
+
+                let secret = bigint::modexp(&base, &(&keypair.private + u * x), n);
+
+                self.state = ClientState::AwaitingResult;
+
+                Ok(Some(Message::Proof(proof(&session_key(&secret), &salt))))
+            }
+            (ClientState::AwaitingResult, Message::Result(ok)) => {
+                self.state = ClientState::Done(ok);
+                Ok(None)
+            }
+            _ => Err(Error::Protocol("Unexpected message")),
+        }
+    }
+
+    /// Returns whether the server accepted the client.
+    pub fn is_authenticated(&self) -> bool {
+        matches!(self.state, ClientState::Done(true))
+    }
+}
+
+/// The states of a [`Server`](struct.Server.html).
+#[derive(Debug, Clone)]
+enum ServerState {
+    AwaitingHello,
+    AwaitingProof(Vec<u8>),
+    Done(bool),
+}
+
+/// A registered user, as stored by the server.
+#[derive(Debug, Clone)]
+struct Verifier {
+    salt: Vec<u8>,
+    v: BigUint,
+}
+
+/// The server side of SRP.
+#[derive(Debug, Clone)]
+pub struct Server {
+    params: SrpParams,
+    users: HashMap<String, Verifier>,
+    state: ServerState,
+}
+
+impl Server {
+    /// Creates a server without any registered user.
+    pub fn new(params: SrpParams) -> Self {
+        Server {
+            params,
+            users: HashMap::new(),
+            state: ServerState::AwaitingHello,
+        }
+    }
+
+    /// Registers a user, storing a random salt and the verifier `v = g^x mod N`.
+    pub fn register<E, P>(&mut self, email: E, password: P)
+    where
+        E: Into<String>,
+        P: AsRef<[u8]>,
+    {
+        let salt = rand::random::<[u8; 16]>().to_vec();
+        let x = private_key(&salt, password.as_ref());
+        let v = bigint::modexp(&self.params.group.g, &x, &self.params.group.p);
+
+        self.users.insert(email.into(), Verifier { salt, v });
+    }
+
+    /// Handles a message from the client, returning the reply.
+    pub fn handle(&mut self, message: Message) -> Result<Message> {
+        match (&self.state, message) {
+            (ServerState::AwaitingHello, Message::Hello { email, public }) => {
+                let Verifier { salt, v } = self
+                    .users
+                    .get(&email)
+                    .cloned()
+                    .ok_or(Error::Protocol("Unknown user"))?;
+
+                let SrpParams { group, k, variant } = &self.params;
+                let n = &group.p;
+
+                // B = k * v + g^b mod N, or B = g^b mod N with a random u
+                let keypair = group.generate_keypair();
+                let (public_b, u) = match variant {
+                    Variant::Standard => {
+                        let public_b = (k * &v + &keypair.public) % n;
+                        let u = scrambler(&public, &public_b);
+
+                        (public_b, u)
+                    }
+                    Variant::Simplified => (
+                        keypair.public.clone(),
+                        bigint::from_bytes(rand::random::<[u8; 16]>()),
+                    ),
+                };
+
+                // S = (A * v^u) ^ b mod N
+                let base = public * bigint::modexp(&v, &u, n) % n;
+                let secret = bigint::modexp(&base, &keypair.private, n);
+
+                self.state = ServerState::AwaitingProof(proof(&session_key(&secret), &salt));
+
+                Ok(Message::Challenge {
+                    salt,
+                    public: public_b,
+                    u: match variant {
+                        Variant::Standard => None,
+                        Variant::Simplified => Some(u),
+                    },
+                })
+            }
+            (ServerState::AwaitingProof(expected), Message::Proof(proof)) => {
+                let ok = *expected == proof;
+                self.state = ServerState::Done(ok);
+
+                Ok(Message::Result(ok))
+            }
+            _ => Err(Error::Protocol("Unexpected message")),
+        }
+    }
+
+    /// Returns whether the session is over.
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, ServerState::Done(_))
+    }
+
+    /// Returns whether the client was authenticated.
+    pub fn is_authenticated(&self) -> bool {
+        matches!(self.state, ServerState::Done(true))
+    }
+}
+
+/// Runs a client over the network, returning whether it was authenticated.
+pub fn run_client(ep: Endpoint<Message>, mut client: Client) -> Result<bool> {
+    ep.send(client.start()?)?;
+
+    while let Some(reply) = client.handle(ep.recv()?)? {
+        ep.send(reply)?;
+    }
+
+    Ok(client.is_authenticated())
+}
+
+/// Runs a server over the network for a single session,
+/// returning whether the client was authenticated.
+pub fn run_server(ep: Endpoint<Message>, mut server: Server) -> Result<bool> {
+    while !server.is_done() {
+        let reply = server.handle(ep.recv()?)?;
+        ep.send(reply)?;
+    }
+
+    Ok(server.is_authenticated())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network;
+
+    fn server(params: SrpParams) -> Server {
+        let mut server = Server::new(params);
+        server.register("alice@example.com", "correct horse battery staple");
+        server
+    }
+
+    fn login(params: SrpParams, password: &'static str) -> (Result<bool>, Result<bool>) {
+        let client = Client::new(params.clone(), "alice@example.com", password);
+        let server = server(params);
+
+        network::run(
+            move |ep| run_client(ep, client),
+            move |ep| run_server(ep, server),
+        )
+    }
+
+    #[test]
+    fn login_succeeds() {
+        for params in [SrpParams::nist(), SrpParams::simplified()] {
+            let (client, server) = login(params, "correct horse battery staple");
+
+            assert!(client.unwrap());
+            assert!(server.unwrap());
+        }
+    }
+
+    #[test]
+    fn wrong_password_fails() {
+        for params in [SrpParams::nist(), SrpParams::simplified()] {
+            let (client, server) = login(params, "hunter2");
+
+            assert!(!client.unwrap());
+            assert!(!server.unwrap());
+        }
+    }
+
+    #[test]
+    fn mismatched_variants_fail() {
+        let mut client = Client::new(
+            SrpParams::nist(),
+            "alice@example.com",
+            "correct horse battery staple",
+        );
+        let challenge = server(SrpParams::simplified())
+            .handle(client.start().unwrap())
+            .unwrap();
+
+        assert_eq!(
+            client.handle(challenge).unwrap_err(),
+            Error::Protocol("Unexpected scrambling parameter")
+        );
+    }
+
+    #[test]
+    fn unknown_user_fails() {
+        let mut client = Client::new(SrpParams::nist(), "bob@example.com", "hunter2");
+
+        assert_eq!(
+            server(SrpParams::nist())
+                .handle(client.start().unwrap())
+                .unwrap_err(),
+            Error::Protocol("Unknown user")
+        );
+    }
+
+    #[test]
+    fn zero_key_bypass() {
+        let n = SrpParams::nist().group.p;
+
+        // With A = 0 (mod N), the server's shared secret is 0 whatever the password
+        for public in [BigUint::from(0u32), n.clone(), &n * 2u32] {
+            let mut server = server(SrpParams::nist());
+
+            let salt = match server
+                .handle(Message::Hello {
+                    email: "alice@example.com".to_string(),
+                    public,
+                })
+                .unwrap()
+            {
+                Message::Challenge { salt, .. } => salt,
+                _ => panic!("expected a challenge"),
+            };
+
+            let forged = proof(&session_key(&BigUint::from(0u32)), &salt);
+
+            assert_eq!(
+                server.handle(Message::Proof(forged)).unwrap(),
+                Message::Result(true)
+            );
+        }
+    }
+}
@@ -1,3 +1,6 @@
 /// Each set is contained in its own module.
 pub mod set1;
 pub mod set2;
+pub mod set3;
+pub mod set4;
+pub mod set5;
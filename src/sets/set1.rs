@@ -1,6 +1,6 @@
 use crate::{
     crypto::{aes::ecb, misc},
-    text, utils, Result,
+    text, utils, Error, Result,
 };
 
 use itertools::Itertools;
@@ -125,7 +125,7 @@ pub fn break_repeating_key_xor<I: AsRef<[u8]>>(input: I) -> Result<String> {
             (key, score)
         })
         .sorted_by(|a, b| b.1.partial_cmp(&a.1).unwrap())
-        .nth(0)
+        .next()
         .unwrap()
         .0;
 
@@ -154,7 +154,7 @@ pub fn detect_aes_in_ecb_mode<I: AsRef<[u8]>>(input: I) -> Result<String> {
             }
         }
     }
-    Err("Ciphertext not detected!".into())
+    Err(Error::AttackFailed("Ciphertext not detected"))
 }
 
 #[cfg(test)]
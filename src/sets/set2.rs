@@ -1,8 +1,10 @@
 use crate::{
+    attacks::{bitflip, ecb},
     crypto::{
         aes::{self, cbc},
         misc,
     },
+    oracle::FnOracle,
     utils, Result,
 };
 
@@ -21,6 +23,7 @@ pub fn implement_cbc_mode<I: AsRef<[u8]>>(input: I) -> Result<String> {
         utils::from_base64(input)?,
         b"YELLOW SUBMARINE",
         None,
+        true,
     )?)?)
 }
 
@@ -43,49 +46,16 @@ pub fn ecb_cbc_detection_oracle() -> Result<bool> {
 /// Byte-at-a-time ECB decryption (Simple)
 pub fn byte_at_a_time_ecb_decryption() -> Result<String> {
     // The oracle function that we will use to crack the encryption
-    let oracle = |i: &[u8]| aes::encrypt_seeded(i, 0xdeadbeef).unwrap();
+    let oracle = FnOracle::new(|i: &[u8]| aes::encrypt_seeded(i, 0xdeadbeef));
 
     // Discover the cipher's block size (should be 16)
-    let bs = misc::discover_block_size(oracle);
+    let bs = misc::discover_block_size(&oracle)?;
     assert_eq!(bs, 16);
 
     // Ensure we are using ECB
-    assert!(aes::is_ecb_encrypted(oracle(&vec![0; bs * 3])));
-
-    // Allocate enough space for the deciphered text
-    let mut deciphered = vec![0u8; oracle(&[]).len()];
-
-    // Break the ciphertext one byte at a time
-    for blk_id in 0..deciphered.len() / bs as usize {
-        let base = blk_id * bs;
-        let end = (blk_id + 1) * bs;
-
-        for i in 0..bs {
-            let n = bs - i;
-
-            let mut test_vec = if blk_id == 0 {
-                [&vec![0; n - 1], &deciphered[..=i]].concat()
-            } else {
-                deciphered[(blk_id - 1) * bs + i + 1..base + i + 1].to_vec()
-            };
-
-            // This is the ciphertext we need to match
-            let hint = oracle(&vec![0; n - 1]);
-
-            // This is every possible matching ciphertext
-            let choices = (0..=255).map(|b| {
-                test_vec[bs - 1] = b;
-                oracle(&test_vec)[..bs].to_vec()
-            });
-
-            for (byte, choice) in choices.enumerate() {
-                if choice == &hint[base..end] {
-                    deciphered[base + i] = byte as u8;
-                    break;
-                }
-            }
-        }
-    }
+    assert!(ecb::is_ecb_oracle(&oracle, bs)?);
+
+    let deciphered = ecb::byte_at_a_time(&oracle, bs)?;
 
     Ok(String::from_utf8(deciphered)?)
 }
@@ -132,7 +102,50 @@ pub fn ecb_cut_and_paste() -> Result<HashMap<String, String>> {
 
     // Decrypting this ciphertext gives us an admin profile.
 
-    Ok(decrypt(&crafted_ciphertext)?)
+    decrypt(&crafted_ciphertext)
+}
+
+/// Set 2 - Challenge 14
+/// Byte-at-a-time ECB decryption (Harder)
+pub fn byte_at_a_time_ecb_decryption_harder() -> Result<String> {
+    // The oracle function that we will use to crack the encryption
+    let oracle = FnOracle::new(|i: &[u8]| aes::encrypt_seeded_prefixed(i, 0xdeadbeef));
+
+    // Discover the cipher's block size (should be 16)
+    let bs = misc::discover_block_size(&oracle)?;
+    assert_eq!(bs, 16);
+
+    // Ensure we are using ECB
+    assert!(ecb::is_ecb_oracle(&oracle, bs)?);
+
+    // Get rid of the prefix and proceed as in the simple case
+    let deciphered = ecb::byte_at_a_time_with_prefix(&oracle, bs)?;
+
+    Ok(String::from_utf8(deciphered)?)
+}
+
+/// Set 2 - Challenge 16
+/// CBC bitflipping attacks
+pub fn cbc_bitflipping_attacks() -> Result<bool> {
+    // The oracle function that we will use to produce ciphertexts
+    let oracle = |userdata: &str| aes::encrypt_comment_cbc(userdata, 0xdeadbeef);
+
+    // The function checking whether we have been granted admin privileges
+    let is_admin = |ct: &[u8]| aes::is_admin_cbc(ct, 0xdeadbeef);
+
+    // The prefix "comment1=cooking%20MCs;userdata=" is exactly two blocks long.
+    //
+    // We provide a whole block of user data that we don't mind being scrambled,
+    // followed by a placeholder for the string we want to inject.
+    // Placeholder characters are chosen so that they are not quoted by the oracle.
+    let userdata = "AAAAAAAAAAAAAAAA?admin?true?";
+    let ciphertext = oracle(userdata)?;
+
+    // Flipping bits in the third ciphertext block changes the fourth plaintext block.
+    let crafted_ciphertext =
+        bitflip::cbc_flip(&ciphertext, 16, 48, b"?admin?true?", b";admin=true;")?;
+
+    is_admin(&crafted_ciphertext)
 }
 
 #[cfg(test)]
@@ -175,8 +188,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn run_byte_at_a_time_ecb_decryption_harder() {
+        assert_eq!(
+            byte_at_a_time_ecb_decryption_harder().unwrap(),
+            "Rollin' in my 5.0\n\
+             With my rag-top down so my hair can blow\n\
+             The girlies on standby waving just to say hi\n\
+             Did you stop? No, I just drove by\n\
+             \u{1}\u{0}\u{0}\u{0}\u{0}\u{0}"
+        );
+    }
+
     #[test]
     fn run_ecb_cut_and_paste() {
         assert_eq!(ecb_cut_and_paste().unwrap()["role"], "admin");
     }
+
+    #[test]
+    fn run_cbc_bitflipping_attacks() {
+        assert!(cbc_bitflipping_attacks().unwrap());
+    }
 }
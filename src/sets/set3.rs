@@ -0,0 +1,295 @@
+use crate::{
+    attacks::padding_oracle,
+    crypto::{
+        aes::{self, cbc, ctr},
+        misc,
+        prng::{self, Mt19937},
+    },
+    oracle::{FnOracle, PaddingQuery},
+    sets::set1,
+    text, utils, Error, Result,
+};
+
+use rand::{seq::SliceRandom, Rng};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Set 3 - Challenge 17
+/// The CBC padding oracle
+pub fn cbc_padding_oracle<I: AsRef<[u8]>>(input: I) -> Result<String> {
+    // Random AES key and IV that will be used for the challenge
+    let key = aes::random_key();
+    let iv = aes::random_key();
+
+    // Pick one of the strings at random and encrypt it
+    let lines = input.as_ref().split(|&c| c == b'\n').collect::<Vec<_>>();
+    let line = lines
+        .choose(&mut rand::thread_rng())
+        .ok_or(Error::InvalidArgument("No input"))?;
+
+    let ciphertext = cbc::encrypt(utils::from_base64(line)?, key, Some(&iv))?;
+
+    // The oracle only tells us whether the padding of the decrypted ciphertext is valid
+    let oracle =
+        FnOracle::new(
+            |q: PaddingQuery| match cbc::decrypt(q.ciphertext, key, Some(q.iv), true) {
+                Ok(_) => Ok(true),
+                Err(Error::Padding(_)) => Ok(false),
+                Err(e) => Err(e),
+            },
+        );
+
+    let plaintext = padding_oracle::decrypt(&oracle, &iv, &ciphertext)?;
+
+    Ok(String::from_utf8(misc::pkcs7_unpad(plaintext, 16)?)?)
+}
+
+/// Minimum number of ciphertexts covering a position for the keystream to be refined there.
+const MIN_CONTRIBUTORS: usize = 2;
+
+/// Encrypts every line of Base64-encoded input with AES-CTR, using a random key and nonce 0.
+fn encrypt_lines_fixed_nonce<I: AsRef<[u8]>>(input: I) -> Result<Vec<Vec<u8>>> {
+    let key = aes::random_key();
+
+    input
+        .as_ref()
+        .split(|&c| c == b'\n')
+        .map(|line| ctr::encrypt(utils::from_base64(line)?, key, 0))
+        .collect()
+}
+
+/// Recovers the keystream shared by ciphertexts encrypted under the same CTR key and nonce.
+///
+/// The ciphertexts are truncated to a common length and transposed, and each
+/// column is broken as single-byte XOR. If `refine` is true, the keystream is then
+/// extended over the ragged tail, one byte at a time: each guess is scored on the
+/// englishness of the partially decrypted ciphertexts that are long enough, until
+/// too few ciphertexts are left or no guess decrypts to plausible characters.
+fn recover_fixed_nonce_keystream(ciphertexts: &[Vec<u8>], refine: bool) -> Result<Vec<u8>> {
+    let common_len = ciphertexts
+        .iter()
+        .map(|ct| ct.len())
+        .min()
+        .ok_or(Error::InvalidArgument("No input"))?;
+
+    // Transpose the truncated ciphertexts and break each column
+    let mut keystream = (0..common_len)
+        .map(|i| {
+            let column = ciphertexts.iter().map(|ct| ct[i]).collect::<Vec<_>>();
+            set1::xor_cipher(column).map(|(k, _, _)| k)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if !refine {
+        return Ok(keystream);
+    }
+
+    for i in common_len.. {
+        let contributors = ciphertexts
+            .iter()
+            .filter(|ct| ct.len() > i)
+            .collect::<Vec<_>>();
+
+        if contributors.len() < MIN_CONTRIBUTORS {
+            break;
+        }
+
+        // Only consider guesses that decrypt to plausible characters,
+        // then pick the one that makes the partial plaintexts look most like English
+        let score = |k: u8| {
+            let keystream = [&keystream[..], &[k]].concat();
+
+            contributors
+                .iter()
+                .map(|ct| text::englishness(misc::xor(&ct[..=i], &keystream[..])))
+                .filter(|score| !score.is_nan())
+                .sum::<f32>()
+        };
+
+        let best = (0..=255)
+            .filter(|&k| {
+                contributors.iter().all(|ct| {
+                    let c = ct[i] ^ k;
+                    c.is_ascii_alphanumeric() || b" .,;:'!?-".contains(&c)
+                })
+            })
+            .max_by(|&a, &b| score(a).partial_cmp(&score(b)).unwrap());
+
+        match best {
+            Some(k) => keystream.push(k),
+            None => break,
+        }
+    }
+
+    Ok(keystream)
+}
+
+/// Decrypts the ciphertexts with the recovered keystream, as far as it goes.
+fn decrypt_with_keystream(ciphertexts: &[Vec<u8>], keystream: &[u8]) -> Vec<String> {
+    ciphertexts
+        .iter()
+        .map(|ct| {
+            let n = usize::min(ct.len(), keystream.len());
+            String::from_utf8_lossy(&misc::xor(&ct[..n], &keystream[..n])).into_owned()
+        })
+        .collect()
+}
+
+/// Set 3 - Challenge 19
+/// Break fixed-nonce CTR mode using substitutions
+pub fn break_fixed_nonce_ctr_using_substitutions<I: AsRef<[u8]>>(input: I) -> Result<Vec<String>> {
+    let ciphertexts = encrypt_lines_fixed_nonce(input)?;
+    let keystream = recover_fixed_nonce_keystream(&ciphertexts, true)?;
+
+    Ok(decrypt_with_keystream(&ciphertexts, &keystream))
+}
+
+/// Set 3 - Challenge 20
+/// Break fixed-nonce CTR statistically
+pub fn break_fixed_nonce_ctr_statistically<I: AsRef<[u8]>>(input: I) -> Result<Vec<String>> {
+    let ciphertexts = encrypt_lines_fixed_nonce(input)?;
+    let keystream = recover_fixed_nonce_keystream(&ciphertexts, false)?;
+
+    Ok(decrypt_with_keystream(&ciphertexts, &keystream))
+}
+
+/// Set 3 - Challenge 21
+/// Implement the MT19937 Mersenne Twister RNG
+pub fn implement_the_mt19937_mersenne_twister_rng(seed: u32, n: usize) -> Vec<u32> {
+    let mut rng = Mt19937::new(seed);
+    (0..n).map(|_| rng.extract()).collect()
+}
+
+/// Set 3 - Challenge 22
+/// Crack an MT19937 seed
+pub fn crack_an_mt19937_seed() -> Result<bool> {
+    // Instead of actually waiting, pretend the generator was seeded some time ago
+    let seed = (SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as u32)
+        - rand::thread_rng().gen_range(80, 2000);
+
+    let output = Mt19937::new(seed).extract();
+
+    Ok(prng::crack_timestamp_seed(output, 2000) == Some(seed))
+}
+
+/// Set 3 - Challenge 23
+/// Clone an MT19937 RNG from its output
+pub fn clone_an_mt19937_rng_from_its_output() -> Result<bool> {
+    let mut rng = Mt19937::new(rand::random());
+
+    // Tap the generator for enough outputs to recover its whole state
+    let outputs = (0..624).map(|_| rng.extract()).collect::<Vec<_>>();
+    let mut clone =
+        Mt19937::from_outputs(&outputs).ok_or(Error::AttackFailed("Not enough outputs"))?;
+
+    // The clone must predict every subsequent output
+    Ok((0..1000).all(|_| clone.extract() == rng.extract()))
+}
+
+/// Set 3 - Challenge 24
+/// Create the MT19937 stream cipher and break it
+pub fn create_the_mt19937_stream_cipher_and_break_it() -> Result<bool> {
+    let mut rng = rand::thread_rng();
+
+    // Encrypt a known plaintext prefixed by a random number of random characters
+    let seed = rng.gen();
+    let known = [b'A'; 14];
+    let prefix = (0..rng.gen_range(5, 20))
+        .map(|_| rng.gen())
+        .collect::<Vec<u8>>();
+
+    let ciphertext = prng::stream_encrypt([&prefix[..], &known[..]].concat(), seed);
+
+    let seed_recovered = prng::recover_stream_seed(ciphertext, &known) == Some(seed);
+
+    // A password reset token generated from the current time must be detected,
+    // while a truly random one must not
+    let token_detected =
+        prng::is_mt_token(prng::mt_token(16), 60) && !prng::is_mt_token(aes::random_key(), 60);
+
+    Ok(seed_recovered && token_detected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_cbc_padding_oracle() {
+        let input = &include_bytes!("../../data/17.txt")[..];
+
+        let expected = input
+            .split(|&c| c == b'\n')
+            .map(|line| String::from_utf8(utils::from_base64(line).unwrap()).unwrap())
+            .collect::<Vec<_>>();
+
+        for _ in 0..5 {
+            assert!(expected.contains(&cbc_padding_oracle(input).unwrap()));
+        }
+    }
+
+    /// Returns the fraction of decrypted characters that were recovered correctly.
+    fn accuracy<I: AsRef<[u8]>>(input: I, recovered: &[String]) -> f32 {
+        let (mut good, mut total) = (0, 0);
+
+        for (line, rec) in input.as_ref().split(|&c| c == b'\n').zip(recovered) {
+            let line = utils::from_base64(line).unwrap();
+
+            good += line
+                .iter()
+                .zip(rec.bytes())
+                .filter(|(a, b)| *a == b)
+                .count();
+            total += usize::min(line.len(), rec.len());
+        }
+
+        good as f32 / total as f32
+    }
+
+    #[test]
+    fn run_break_fixed_nonce_ctr_using_substitutions() {
+        let input = &include_bytes!("../../data/19.txt")[..];
+        let recovered = break_fixed_nonce_ctr_using_substitutions(input).unwrap();
+
+        assert!(accuracy(input, &recovered) > 0.9);
+    }
+
+    #[test]
+    fn run_break_fixed_nonce_ctr_statistically() {
+        let input = &include_bytes!("../../data/20.txt")[..];
+        let recovered = break_fixed_nonce_ctr_statistically(input).unwrap();
+
+        assert!(accuracy(input, &recovered) > 0.9);
+    }
+
+    #[test]
+    fn run_implement_the_mt19937_mersenne_twister_rng() {
+        assert_eq!(
+            implement_the_mt19937_mersenne_twister_rng(5489, 5),
+            vec![
+                3_499_211_612,
+                581_869_302,
+                3_890_346_734,
+                3_586_334_585,
+                545_404_204
+            ]
+        );
+    }
+
+    #[test]
+    fn run_crack_an_mt19937_seed() {
+        assert!(crack_an_mt19937_seed().unwrap());
+    }
+
+    #[test]
+    fn run_clone_an_mt19937_rng_from_its_output() {
+        assert!(clone_an_mt19937_rng_from_its_output().unwrap());
+    }
+
+    #[test]
+    fn run_create_the_mt19937_stream_cipher_and_break_it() {
+        assert!(create_the_mt19937_stream_cipher_and_break_it().unwrap());
+    }
+}
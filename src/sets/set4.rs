@@ -0,0 +1,232 @@
+use crate::{
+    attacks::{self, bitflip, length_extension, timing},
+    crypto::{
+        aes::{self, cbc, ctr},
+        hash::{
+            md4::{self, Md4},
+            sha1::{self, Sha1},
+        },
+    },
+    http,
+    oracle::{FnOracle, MacQuery, Oracle},
+    sets::set1,
+    Result,
+};
+
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+/// Set 4 - Challenge 25
+/// Break "random access read/write" AES CTR
+pub fn break_random_access_read_write_aes_ctr<I: AsRef<[u8]>>(input: I) -> Result<String> {
+    // Recover the plaintext from Challenge 7 and re-encrypt it under CTR with a random key
+    let plaintext = set1::aes_in_ecb_mode(input)?;
+
+    let key = aes::random_key();
+    let nonce = rand::random();
+    let ciphertext = ctr::encrypt(plaintext, key, nonce)?;
+
+    // The attacker only gets to see the ciphertext and to edit it
+    let oracle = FnOracle::new(|(offset, newtext): (usize, &[u8])| {
+        ctr::edit(&ciphertext, key, nonce, offset, newtext)
+    });
+
+    let recovered = attacks::ctr::decrypt_with_edit(&oracle, &ciphertext)?;
+
+    Ok(String::from_utf8(recovered)?)
+}
+
+/// Set 4 - Challenge 26
+/// CTR bitflipping
+pub fn ctr_bitflipping() -> Result<bool> {
+    // The oracle function that we will use to produce ciphertexts
+    let oracle = |userdata: &str| aes::encrypt_comment_ctr(userdata, 0xdeadbeef);
+
+    // The function checking whether we have been granted admin privileges
+    let is_admin = |ct: &[u8]| aes::is_admin_ctr(ct, 0xdeadbeef);
+
+    // The user data starts right after the 32-byte prefix "comment1=cooking%20MCs;userdata=".
+    // Unlike CBC, no plaintext gets scrambled, so we only need the placeholder.
+    let ciphertext = oracle("?admin?true?")?;
+
+    let crafted_ciphertext = bitflip::ctr_flip(&ciphertext, 32, b"?admin?true?", b";admin=true;")?;
+
+    is_admin(&crafted_ciphertext)
+}
+
+/// Set 4 - Challenge 27
+/// Recover the key from CBC with IV=Key
+pub fn recover_the_key_from_cbc_with_iv_key() -> Result<bool> {
+    // The oracle function that we will use to produce ciphertexts
+    let oracle = |userdata: &str| aes::encrypt_comment_cbc_iv_key(userdata, 0xdeadbeef);
+
+    // The receiver complains, leaking the plaintext, when it finds high-ASCII characters
+    let receiver = FnOracle::new(|ct: &[u8]| aes::is_admin_cbc_iv_key(ct, 0xdeadbeef));
+
+    let ciphertext = oracle("")?;
+    let key = attacks::cbc::recover_iv_key(&receiver, &ciphertext, 16)?;
+
+    // Knowing the key, we can encrypt whatever we want
+    let forged = cbc::encrypt("comment1=foo;admin=true;comment2=bar", &key, Some(&key))?;
+
+    receiver.query(&forged)
+}
+
+/// Set 4 - Challenge 28
+/// Implement a SHA-1 keyed MAC
+pub fn implement_a_sha1_keyed_mac() -> Result<bool> {
+    let key = aes::random_key();
+    let message = b"We all live in a yellow submarine";
+    let mac = sha1::secret_prefix_mac(key, &message[..]);
+
+    // Tampering with the message invalidates the MAC, and the MAC cannot be produced without the key
+    let tampered = sha1::secret_prefix_mac(key, b"We all live in a purple submarine") != mac;
+    let keyless = sha1::digest(&message[..]) != mac;
+
+    Ok(tampered && keyless && sha1::secret_prefix_mac(key, &message[..]) == mac)
+}
+
+/// Set 4 - Challenge 29
+/// Break a SHA-1 keyed MAC using length extension
+pub fn break_a_sha1_keyed_mac_using_length_extension() -> Result<bool> {
+    let mut rng = rand::thread_rng();
+
+    // A secret key of unknown length
+    let key = (0..rng.gen_range(1, 33))
+        .map(|_| rng.gen())
+        .collect::<Vec<u8>>();
+
+    let message = b"comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon";
+    let mac = sha1::secret_prefix_mac(&key, &message[..]);
+
+    // The verifier only tells us whether a message is correctly authenticated
+    let verifier =
+        FnOracle::new(|q: MacQuery| Ok(sha1::secret_prefix_mac(&key, q.message) == q.mac));
+
+    let (forged, _) =
+        length_extension::forge::<Sha1, _>(&verifier, &message[..], &mac, b";admin=true", 64)?;
+
+    Ok(forged.ends_with(b";admin=true"))
+}
+
+/// Set 4 - Challenge 30
+/// Break an MD4 keyed MAC using length extension
+pub fn break_an_md4_keyed_mac_using_length_extension() -> Result<bool> {
+    let mut rng = rand::thread_rng();
+
+    // A secret key of unknown length
+    let key = (0..rng.gen_range(1, 33))
+        .map(|_| rng.gen())
+        .collect::<Vec<u8>>();
+
+    let message = b"comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon";
+    let mac = md4::secret_prefix_mac(&key, &message[..]);
+
+    // The verifier only tells us whether a message is correctly authenticated
+    let verifier =
+        FnOracle::new(|q: MacQuery| Ok(md4::secret_prefix_mac(&key, q.message) == q.mac));
+
+    let (forged, _) =
+        length_extension::forge::<Md4, _>(&verifier, &message[..], &mac, b";admin=true", 64)?;
+
+    Ok(forged.ends_with(b";admin=true"))
+}
+
+/// Recovers the HMAC-SHA1 of a file name, truncated to `mac_len` bytes, from a server
+/// leaking timing information through its signature comparison, and returns whether
+/// the server accepts it.
+fn break_hmac_sha1_timing_leak(delay: Duration, samples: usize, mac_len: usize) -> Result<bool> {
+    let server = http::Server::spawn_truncated(aes::random_key(), delay, mac_len)?;
+    let file = "foo";
+
+    // Time each request to the server
+    let oracle = FnOracle::new(|signature: &[u8]| {
+        let start = Instant::now();
+        let valid = http::verify(server.addr(), file, signature)?;
+        Ok((valid, start.elapsed()))
+    });
+
+    let signature = timing::recover_mac(&oracle, mac_len, samples)?;
+
+    http::verify(server.addr(), file, &signature)
+}
+
+/// Set 4 - Challenge 31
+/// Implement and break HMAC-SHA1 with an artificial timing leak
+pub fn implement_and_break_hmac_sha1_with_an_artificial_timing_leak() -> Result<bool> {
+    break_hmac_sha1_timing_leak(Duration::from_millis(50), 1, sha1::OUTPUT_SIZE)
+}
+
+/// Set 4 - Challenge 32
+/// Break HMAC-SHA1 with a slightly less artificial timing leak
+///
+/// With a smaller delay, each guess needs to be timed several times
+/// to tell it apart from the noise.
+pub fn break_hmac_sha1_with_a_slightly_less_artificial_timing_leak(
+    delay: Duration,
+    samples: usize,
+) -> Result<bool> {
+    break_hmac_sha1_timing_leak(delay, samples, sha1::OUTPUT_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_break_random_access_read_write_aes_ctr() {
+        let input = &include_bytes!("../../data/7.txt")[..];
+
+        assert_eq!(
+            break_random_access_read_write_aes_ctr(input).unwrap(),
+            set1::aes_in_ecb_mode(input).unwrap()
+        );
+    }
+
+    #[test]
+    fn run_ctr_bitflipping() {
+        assert!(ctr_bitflipping().unwrap());
+    }
+
+    #[test]
+    fn run_recover_the_key_from_cbc_with_iv_key() {
+        assert!(recover_the_key_from_cbc_with_iv_key().unwrap());
+    }
+
+    #[test]
+    fn run_implement_a_sha1_keyed_mac() {
+        assert!(implement_a_sha1_keyed_mac().unwrap());
+    }
+
+    #[test]
+    fn run_break_a_sha1_keyed_mac_using_length_extension() {
+        assert!(break_a_sha1_keyed_mac_using_length_extension().unwrap());
+    }
+
+    #[test]
+    fn run_break_an_md4_keyed_mac_using_length_extension() {
+        assert!(break_an_md4_keyed_mac_using_length_extension().unwrap());
+    }
+
+    #[test]
+    fn break_truncated_hmac_sha1_timing_leak() {
+        // Same as the challenges, on a 3-byte signature, so that it runs in a few seconds
+        assert!(break_hmac_sha1_timing_leak(Duration::from_millis(5), 3, 3).unwrap());
+    }
+
+    #[test]
+    #[ignore = "takes about 40 minutes"]
+    fn run_implement_and_break_hmac_sha1_with_an_artificial_timing_leak() {
+        assert!(implement_and_break_hmac_sha1_with_an_artificial_timing_leak().unwrap());
+    }
+
+    #[test]
+    #[ignore = "takes about 20 minutes"]
+    fn run_break_hmac_sha1_with_a_slightly_less_artificial_timing_leak() {
+        assert!(break_hmac_sha1_with_a_slightly_less_artificial_timing_leak(
+            Duration::from_millis(5),
+            5
+        )
+        .unwrap());
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    attacks::{
+        dh_mitm::{self, MaliciousG},
+        srp_dictionary,
+    },
+    crypto::{
+        bigint::BigUint,
+        dh::{self, DhGroup},
+    },
+    network,
+    protocols::{
+        dh_echo,
+        srp::{self, Client, Message, Server, SrpParams},
+    },
+    Error, Result,
+};
+
+use rand::seq::SliceRandom;
+use std::{fs, path::Path};
+
+/// Set 5 - Challenge 33
+/// Implement Diffie-Hellman
+pub fn implement_diffie_hellman() -> Result<bool> {
+    // Warm up with small numbers
+    let small = DhGroup::new(BigUint::from(37u32), BigUint::from(5u32), None);
+    let (a, b) = (small.generate_keypair(), small.generate_keypair());
+    let small_agree = small.shared_secret(&a, &b.public) == small.shared_secret(&b, &a.public);
+
+    // Then switch to the NIST parameters
+    let group = DhGroup::nist();
+    let (a, b) = (group.generate_keypair(), group.generate_keypair());
+
+    let s_a = group.shared_secret(&a, &b.public);
+    let s_b = group.shared_secret(&b, &a.public);
+
+    // Both parties derive the same key, and can talk to each other
+    let message = "Sensitive message from A to B";
+    let received = dh::decrypt(&s_b, dh::encrypt(&s_a, message)?)?;
+
+    Ok(small_agree && s_a == s_b && received == message.as_bytes())
+}
+
+/// Set 5 - Challenge 34
+/// Implement a MITM key-fixing attack on Diffie-Hellman with parameter injection
+pub fn implement_a_mitm_key_fixing_attack_on_diffie_hellman() -> Result<bool> {
+    let message = b"Sensitive message from A to B";
+
+    let (echoed, received, intercepted) = network::run_with_mitm(
+        move |ep| dh_echo::initiator(ep, DhGroup::nist(), message),
+        dh_echo::responder,
+        dh_mitm::key_fixing,
+    );
+
+    // Neither party noticed anything, and the attacker read everything
+    Ok(echoed? == message && received? == message && intercepted?.iter().all(|m| m == message))
+}
+
+/// Set 5 - Challenge 35
+/// Implement DH with negotiated groups, and break with malicious "g" parameters
+pub fn implement_dh_with_negotiated_groups_and_break_with_malicious_g_parameters() -> Result<bool> {
+    let message = b"Sensitive message from A to B";
+
+    for &g in &[MaliciousG::One, MaliciousG::P, MaliciousG::PMinusOne] {
+        let (echoed, received, intercepted) = network::run_with_mitm(
+            move |ep| dh_echo::negotiating_initiator(ep, DhGroup::nist(), message),
+            dh_echo::negotiating_responder,
+            |mitm| dh_mitm::malicious_g(mitm, g),
+        );
+
+        if echoed? != message || received? != message || intercepted?.iter().any(|m| m != message) {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Set 5 - Challenge 36
+/// Implement Secure Remote Password (SRP)
+pub fn implement_secure_remote_password() -> Result<bool> {
+    let mut server = Server::new(SrpParams::nist());
+    server.register("alice@example.com", "correct horse battery staple");
+
+    let client = Client::new(
+        SrpParams::nist(),
+        "alice@example.com",
+        "correct horse battery staple",
+    );
+
+    let (client_ok, server_ok) = network::run(
+        move |ep| srp::run_client(ep, client),
+        move |ep| srp::run_server(ep, server),
+    );
+
+    Ok(client_ok? && server_ok?)
+}
+
+/// Set 5 - Challenge 37
+/// Break SRP with a zero key
+pub fn break_srp_with_a_zero_key() -> Result<bool> {
+    let n = SrpParams::nist().group.p;
+
+    for public in [BigUint::from(0u32), n.clone(), &n * 2u32, &n * 3u32] {
+        let mut server = Server::new(SrpParams::nist());
+        server.register("alice@example.com", "correct horse battery staple");
+
+        // Any public key that is a multiple of N forces the server's secret to 0,
+        // so the attacker can compute the proof without knowing the password
+        let (attacker_ok, server_ok) = network::run(
+            move |ep| {
+                ep.send(Message::Hello {
+                    email: "alice@example.com".to_string(),
+                    public,
+                })?;
+
+                let salt = match ep.recv()? {
+                    Message::Challenge { salt, .. } => salt,
+                    _ => return Err(Error::Protocol("Unexpected message")),
+                };
+
+                let key = srp::session_key(&BigUint::from(0u32));
+                ep.send(Message::Proof(srp::proof(&key, &salt)))?;
+
+                match ep.recv()? {
+                    Message::Result(ok) => Ok(ok),
+                    _ => Err(Error::Protocol("Unexpected message")),
+                }
+            },
+            move |ep| srp::run_server(ep, server),
+        );
+
+        if !attacker_ok? || !server_ok? {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Set 5 - Challenge 38
+/// Offline dictionary attack on simplified SRP
+pub fn offline_dictionary_attack_on_simplified_srp<P: AsRef<Path>>(wordlist: P) -> Result<bool> {
+    // The victim uses one of the words in the list as password
+    let words = fs::read_to_string(&wordlist)?;
+    let password = words
+        .lines()
+        .collect::<Vec<_>>()
+        .choose(&mut rand::thread_rng())
+        .ok_or(Error::InvalidArgument("Empty word list"))?
+        .to_string();
+
+    // The protocol works as intended against the legitimate server
+    let mut server = Server::new(SrpParams::simplified());
+    server.register("alice@example.com", &password);
+
+    let client = Client::new(SrpParams::simplified(), "alice@example.com", &password);
+
+    let (client_ok, server_ok) = network::run(
+        move |ep| srp::run_client(ep, client),
+        move |ep| srp::run_server(ep, server),
+    );
+
+    if !client_ok? || !server_ok? {
+        return Ok(false);
+    }
+
+    // Posing as the server, the attacker captures enough to crack the password offline
+    let client = Client::new(SrpParams::simplified(), "alice@example.com", &password);
+
+    let (_, transcript) = network::run(
+        move |ep| srp::run_client(ep, client),
+        |ep| srp_dictionary::impersonate_server(ep, &SrpParams::simplified()),
+    );
+
+    let cracked = srp_dictionary::crack_password(&transcript?, &SrpParams::simplified(), wordlist)?;
+
+    Ok(cracked == password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_implement_diffie_hellman() {
+        assert!(implement_diffie_hellman().unwrap());
+    }
+
+    #[test]
+    fn run_implement_a_mitm_key_fixing_attack_on_diffie_hellman() {
+        assert!(implement_a_mitm_key_fixing_attack_on_diffie_hellman().unwrap());
+    }
+
+    #[test]
+    fn run_implement_dh_with_negotiated_groups_and_break_with_malicious_g_parameters() {
+        assert!(
+            implement_dh_with_negotiated_groups_and_break_with_malicious_g_parameters().unwrap()
+        );
+    }
+
+    #[test]
+    fn run_implement_secure_remote_password() {
+        assert!(implement_secure_remote_password().unwrap());
+    }
+
+    #[test]
+    fn run_break_srp_with_a_zero_key() {
+        assert!(break_srp_with_a_zero_key().unwrap());
+    }
+
+    #[test]
+    fn run_offline_dictionary_attack_on_simplified_srp() {
+        assert!(offline_dictionary_attack_on_simplified_srp("data/38.txt").unwrap());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Result;
+use crate::{Error, Result};
 
 use std::collections::HashMap;
 
@@ -22,8 +22,8 @@ pub fn from_base64<I: AsRef<[u8]>>(input: I) -> Result<Vec<u8>> {
 /// corresponding elements of the slices.
 pub fn hamming<I: AsRef<[u8]>>(a: I, b: I) -> u32 {
     a.as_ref()
-        .into_iter()
-        .zip(b.as_ref().into_iter())
+        .iter()
+        .zip(b.as_ref().iter())
         .map(|(x, y)| (x ^ y).count_ones())
         .sum::<u32>()
 }
@@ -42,19 +42,68 @@ pub fn profile_for<I: AsRef<str>>(email: I) -> String {
     format!("email={}&uid=10&role=user", email)
 }
 
+/// Generates the comment string containing the specified user data.
+///
+/// The user data is quoted before encoding, by escaping ; and = characters.
+pub fn comment_for<I: AsRef<str>>(userdata: I) -> String {
+    // Quote user data
+    let userdata = userdata.as_ref().replace(';', "%3B").replace('=', "%3D");
+
+    format!(
+        "comment1=cooking%20MCs;userdata={};comment2=%20like%20a%20pound%20of%20bacon",
+        userdata
+    )
+}
+
+/// Returns whether the (possibly corrupted) comment string grants admin privileges.
+///
+/// The comment must contain a `;admin=true;` field, delimited on both sides.
+/// Segments that are not `key=value` pairs, such as scrambled blocks, are ignored.
+pub fn is_admin<I: AsRef<[u8]>>(comment: I) -> bool {
+    let comment = String::from_utf8_lossy(comment.as_ref());
+
+    // Only keep the fields which are followed and preceded by a separator
+    match (comment.find(';'), comment.rfind(';')) {
+        (Some(start), Some(end)) if start < end => {
+            parse_kv_lenient(&comment[start + 1..end], ';')
+                .get("admin")
+                .map(String::as_str)
+                == Some("true")
+        }
+        _ => false,
+    }
+}
+
 /// Parses a key-value-encoded profile string into its components.
 pub fn parse_kv_encoded<I: AsRef<str>>(s: I) -> Result<HashMap<String, String>> {
+    parse_kv_separated(s, '&')
+}
+
+/// Parses a string of `key=value` pairs separated by `sep` into its components.
+pub fn parse_kv_separated<I: AsRef<str>>(s: I, sep: char) -> Result<HashMap<String, String>> {
     let mut map = HashMap::new();
-    for pairs in s.as_ref().split('&') {
+    for pairs in s.as_ref().split(sep) {
         let mut kv = pairs.split('=');
         map.insert(
-            kv.next().ok_or("Missing key")?.to_owned(),
-            kv.next().ok_or("Missing value")?.to_owned(),
+            kv.next().ok_or(Error::Parse("Missing key"))?.to_owned(),
+            kv.next().ok_or(Error::Parse("Missing value"))?.to_owned(),
         );
     }
     Ok(map)
 }
 
+/// Parses a string of `key=value` pairs separated by `sep` into its components,
+/// skipping any segment which does not contain a `=`.
+pub fn parse_kv_lenient<I: AsRef<str>>(s: I, sep: char) -> HashMap<String, String> {
+    s.as_ref()
+        .split(sep)
+        .filter_map(|pair| {
+            let mut kv = pair.splitn(2, '=');
+            Some((kv.next()?.to_owned(), kv.next()?.to_owned()))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -85,6 +134,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn comment_for_works() {
+        for (userdata, out) in vec![
+            ("", ""),
+            ("foo", "foo"),
+            (";admin=true;", "%3Badmin%3Dtrue%3B"),
+            ("%3B", "%3B"),
+        ]
+        .into_iter()
+        {
+            assert_eq!(
+                comment_for(userdata),
+                format!(
+                    "comment1=cooking%20MCs;userdata={};comment2=%20like%20a%20pound%20of%20bacon",
+                    out
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn is_admin_works() {
+        assert!(is_admin(b"comment1=\xff\x00;admin=true;comment2=foo"));
+        assert!(is_admin(b"comment1=foo;\xff;\x00;admin=true;;\xff\x00"));
+        assert!(!is_admin(comment_for(";admin=true;")));
+        assert!(!is_admin("admin=false;role=admin"));
+        assert!(!is_admin("role=user;admin=true"));
+        assert!(!is_admin("admin=true;role=user"));
+    }
+
     #[test]
     fn parse_kv_encoded_works() {
         assert_eq!(
@@ -100,4 +179,30 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn parse_kv_separated_works() {
+        let comment = parse_kv_separated(comment_for("foo;admin=true"), ';').unwrap();
+
+        assert_eq!(comment["userdata"], "foo%3Badmin%3Dtrue");
+        assert_eq!(comment.get("admin"), None);
+        assert_eq!(comment.len(), 3);
+    }
+
+    #[test]
+    fn parse_kv_lenient_works() {
+        let comment = parse_kv_lenient("foo;admin=true;;bar=a=b;\u{fffd}", ';');
+
+        assert_eq!(comment["admin"], "true");
+        assert_eq!(comment["bar"], "a=b");
+        assert_eq!(comment.len(), 2);
+    }
+
+    #[test]
+    fn parse_kv_encoded_fails() {
+        assert_eq!(
+            parse_kv_encoded("email=foo@bar.com&uid").unwrap_err(),
+            Error::Parse("Missing value")
+        );
+    }
 }